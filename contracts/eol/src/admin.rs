@@ -1,6 +1,11 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{ensure, Addr};
+use cosmwasm_std::{ensure, Addr, StdResult, Timestamp};
 
+use crate::config::Config;
+use crate::eol::Duration;
+use crate::msg::{DenomRemovalTarget, TrackedDenom};
+use crate::pause::PauseScope;
+use crate::price::PriceResolutionConfig;
 use crate::ContractError;
 
 #[cw_serde]
@@ -109,6 +114,83 @@ impl Admin {
     }
 }
 
+/// Admin change held back by the admin timelock
+#[cw_serde]
+pub enum AdminOp {
+    SetConfig {
+        config: Config,
+    },
+    SetPriceResolutionConfig {
+        price_resolution_config: PriceResolutionConfig,
+    },
+    SetTrackedDenoms {
+        tracked_denoms: Vec<TrackedDenom>,
+    },
+    RemoveTrackedDenoms {
+        target: DenomRemovalTarget,
+    },
+    /// Applies right away even under a timelock, so activity can be halted without delay
+    Pause {
+        scope: PauseScope,
+    },
+    Unpause {
+        scope: PauseScope,
+    },
+    /// Change the timelock itself, which is held back by the current one
+    SetTimelock {
+        delay: Duration,
+    },
+}
+
+impl AdminOp {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        match self {
+            AdminOp::SetConfig { config } => config.validate()?,
+            AdminOp::SetPriceResolutionConfig {
+                price_resolution_config,
+            } => price_resolution_config.validate()?,
+            AdminOp::SetTrackedDenoms { .. }
+            | AdminOp::RemoveTrackedDenoms { .. }
+            | AdminOp::Pause { .. }
+            | AdminOp::Unpause { .. }
+            | AdminOp::SetTimelock { .. } => {}
+        }
+
+        Ok(())
+    }
+
+    /// Whether `self` can be applied directly while a timelock is set
+    pub fn bypasses_timelock(&self) -> bool {
+        matches!(self, AdminOp::Pause { .. })
+    }
+}
+
+/// Admin operation queued until `executable_at`
+#[cw_serde]
+pub struct PendingAdminOp {
+    pub op: AdminOp,
+    pub executable_at: Timestamp,
+}
+
+impl PendingAdminOp {
+    pub fn new(op: AdminOp, queued_at: Timestamp, timelock: Duration) -> StdResult<Self> {
+        Ok(Self {
+            op,
+            executable_at: timelock.after(queued_at)?,
+        })
+    }
+
+    pub fn ensure_executable(&self, now: Timestamp) -> Result<(), ContractError> {
+        ensure!(
+            now >= self.executable_at,
+            ContractError::AdminOpNotReady {
+                executable_at: self.executable_at
+            }
+        );
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -332,4 +414,52 @@ mod tests {
         let admin = Admin::Transferring { current, candidate };
         assert!(admin.authorized_cancel_admin_transfer(&other_addr).is_err());
     }
+
+    #[test]
+    fn pending_admin_op_executable() {
+        let queued_at = Timestamp::from_seconds(1_000);
+        let op = PendingAdminOp::new(
+            AdminOp::Pause {
+                scope: PauseScope::Beneficiaries,
+            },
+            queued_at,
+            Duration::from_seconds(100).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            op.ensure_executable(queued_at.plus_seconds(99)),
+            Err(ContractError::AdminOpNotReady {
+                executable_at: queued_at.plus_seconds(100)
+            })
+        );
+        assert!(op.ensure_executable(queued_at.plus_seconds(100)).is_ok());
+
+        // a timelock running past the latest timestamp can't be queued against
+        assert!(PendingAdminOp::new(
+            AdminOp::Pause {
+                scope: PauseScope::Beneficiaries,
+            },
+            queued_at,
+            Duration::from_nanos(u64::MAX),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn admin_op_validation() {
+        let op = AdminOp::SetConfig {
+            config: Config {
+                min_inactivity_period: Duration::from_seconds(2).unwrap(),
+                max_inactivity_period: Duration::from_seconds(1).unwrap(),
+                max_beneficiaries: 1,
+            },
+        };
+        assert!(op.validate().is_err());
+
+        let op = AdminOp::SetTimelock {
+            delay: Duration::from_seconds(0).unwrap(),
+        };
+        assert!(op.validate().is_ok());
+    }
 }
@@ -1,8 +1,11 @@
 use std::str::FromStr;
 
-use cosmwasm_std::{from_json, StdError};
+use cosmwasm_std::{ensure, from_json, StdError};
 use osmosis_std::types::osmosis::smartaccount::v1beta1::AccountAuthenticator;
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Deserialize, Serialize,
+};
 
 use thiserror::Error;
 
@@ -16,6 +19,14 @@ pub enum CompositeAuthenticatorError {
 
     #[error("Empty requested path")]
     EmptyRequestedPath,
+
+    #[error("Composite authenticators nested deeper than {max_depth} levels are not supported")]
+    CompositeTooDeep { max_depth: usize },
+
+    #[error(
+        "Composite authenticators with more than {max_width} sub-authenticators are not supported"
+    )]
+    CompositeTooWide { max_width: usize },
 }
 
 impl CompositeAuthenticatorError {
@@ -26,6 +37,64 @@ impl CompositeAuthenticatorError {
     }
 }
 
+/// Type of the authenticators backed by a cosmwasm contract
+pub const COSMWASM_AUTHENTICATOR: &str = "CosmwasmAuthenticatorV1";
+
+pub const DEFAULT_MAX_DEPTH: usize = 8;
+pub const DEFAULT_MAX_WIDTH: usize = 1024;
+
+/// Bounds on the composite authenticators walked through within the hooks,
+/// where gas is tight and configs are chosen by the account
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CompositeLimits {
+    /// Most levels of sub-authenticators below the root
+    pub max_depth: usize,
+    /// Most sub-authenticators of a single composite authenticator
+    pub max_width: usize,
+}
+
+impl Default for CompositeLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_width: DEFAULT_MAX_WIDTH,
+        }
+    }
+}
+
+impl CompositeLimits {
+    pub fn ensure_depth(&self, depth: usize) -> Result<(), CompositeAuthenticatorError> {
+        ensure!(
+            depth <= self.max_depth,
+            CompositeAuthenticatorError::CompositeTooDeep {
+                max_depth: self.max_depth
+            }
+        );
+        Ok(())
+    }
+
+    pub fn ensure_width(&self, width: usize) -> Result<(), CompositeAuthenticatorError> {
+        ensure!(
+            width <= self.max_width,
+            CompositeAuthenticatorError::CompositeTooWide {
+                max_width: self.max_width
+            }
+        );
+        Ok(())
+    }
+
+    /// Ensure `index` falls within the widest composite authenticator allowed
+    pub fn ensure_index(&self, index: usize) -> Result<(), CompositeAuthenticatorError> {
+        ensure!(
+            index < self.max_width,
+            CompositeAuthenticatorError::CompositeTooWide {
+                max_width: self.max_width
+            }
+        );
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 pub struct CosmwasmAuthenticatorData {
     pub contract: String,
@@ -61,12 +130,12 @@ impl CompositeId {
     pub fn new(root: u64, path: Vec<usize>) -> Self {
         CompositeId { root, path }
     }
-}
 
-impl FromStr for CompositeId {
-    type Err = CompositeAuthenticatorError;
-
-    fn from_str(composite_id: &str) -> Result<Self, Self::Err> {
+    /// Parse `composite_id`, rejecting paths beyond `limits`
+    pub fn parse(
+        composite_id: &str,
+        limits: &CompositeLimits,
+    ) -> Result<Self, CompositeAuthenticatorError> {
         let mut parts = composite_id.split('.');
         let root = parts
             .next()
@@ -74,13 +143,33 @@ impl FromStr for CompositeId {
             .ok_or_else(|| CompositeAuthenticatorError::invalid_composite_id(composite_id))?
             .map_err(|_| CompositeAuthenticatorError::invalid_composite_id(composite_id))?;
 
-        let path = parts
-            .map(|s| s.parse::<usize>())
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|_| CompositeAuthenticatorError::invalid_composite_id(composite_id))?;
+        let mut path = vec![];
+        for part in parts {
+            limits.ensure_depth(path.len() + 1)?;
+
+            let index = part
+                .parse::<usize>()
+                .map_err(|_| CompositeAuthenticatorError::invalid_composite_id(composite_id))?;
+            limits.ensure_index(index)?;
+
+            path.push(index);
+        }
 
         Ok(CompositeId { root, path })
     }
+
+    /// Whether `other` is this authenticator or one nested under it
+    pub fn contains(&self, other: &CompositeId) -> bool {
+        self.root == other.root && other.path.starts_with(&self.path)
+    }
+}
+
+impl FromStr for CompositeId {
+    type Err = CompositeAuthenticatorError;
+
+    fn from_str(composite_id: &str) -> Result<Self, Self::Err> {
+        Self::parse(composite_id, &CompositeLimits::default())
+    }
 }
 
 impl ToString for CompositeId {
@@ -100,65 +189,74 @@ impl ToString for CompositeId {
     }
 }
 
+/// Sub-authenticators listed in the `config` of a composite authenticator,
+/// counted before any gets decoded so overly wide configs are rejected early
+pub fn sub_authenticators(
+    config: &[u8],
+    limits: &CompositeLimits,
+) -> Result<Vec<SubAuthenticatorData>, CompositeAuthenticatorError> {
+    let width = from_json::<Vec<IgnoredAny>>(config)?.len();
+    limits.ensure_width(width)?;
+
+    Ok(from_json(config)?)
+}
+
+/// Sub-authenticator data along with the type of the authenticator it configures
+#[derive(PartialEq, Debug)]
+pub struct ChildAuthenticator<T> {
+    pub r#type: String,
+    pub data: T,
+}
+
 pub trait CompositeAuthenticator {
-    fn child_authenticator_data<T>(self, path: &[usize]) -> Result<T, CompositeAuthenticatorError>
+    fn child_authenticator_data<T>(
+        self,
+        path: &[usize],
+        limits: &CompositeLimits,
+    ) -> Result<ChildAuthenticator<T>, CompositeAuthenticatorError>
     where
         T: DeserializeOwned;
 }
 
 impl CompositeAuthenticator for AccountAuthenticator {
-    fn child_authenticator_data<T>(self, path: &[usize]) -> Result<T, CompositeAuthenticatorError>
+    fn child_authenticator_data<T>(
+        self,
+        path: &[usize],
+        limits: &CompositeLimits,
+    ) -> Result<ChildAuthenticator<T>, CompositeAuthenticatorError>
     where
         T: DeserializeOwned,
     {
-        if path.is_empty() {
-            return from_json(self.config).map_err(CompositeAuthenticatorError::StdError);
-        }
-
-        let root_sub_auths: Vec<SubAuthenticatorData> = from_json(self.config)?;
-        let sub_auths =
-            path.iter()
-                .take(path.len() - 1)
-                .try_fold(root_sub_auths, |parent, &p| {
-                    from_json(
-                        parent
-                            .get(p)
-                            .ok_or_else(|| {
-                                CompositeAuthenticatorError::invalid_composite_id(
-                                    CompositeId::new(self.id, path.to_vec())
-                                        .to_string()
-                                        .as_str(),
-                                )
-                            })?
-                            .config
-                            .as_slice(),
+        // bail out before decoding anything
+        limits.ensure_depth(path.len())?;
+
+        let mut r#type = self.r#type;
+        let mut config = self.config;
+        for &index in path {
+            let sub_auth = sub_authenticators(&config, limits)?
+                .into_iter()
+                .nth(index)
+                .ok_or_else(|| {
+                    CompositeAuthenticatorError::invalid_composite_id(
+                        CompositeId::new(self.id, path.to_vec())
+                            .to_string()
+                            .as_str(),
                     )
-                    .map_err(CompositeAuthenticatorError::StdError)
                 })?;
+            r#type = sub_auth.r#type;
+            config = sub_auth.config;
+        }
 
-        let target_idx = path
-            .last()
-            .ok_or(CompositeAuthenticatorError::EmptyRequestedPath)?;
-
-        let target_data = sub_auths
-            .get(*target_idx)
-            .ok_or_else(|| {
-                CompositeAuthenticatorError::invalid_composite_id(
-                    CompositeId::new(self.id, path.to_vec())
-                        .to_string()
-                        .as_str(),
-                )
-            })?
-            .config
-            .as_slice();
-
-        from_json(target_data).map_err(CompositeAuthenticatorError::StdError)
+        Ok(ChildAuthenticator {
+            r#type,
+            data: from_json(config)?,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{period::Period, spend_limit::SpendLimitParams};
+    use crate::eol::{Duration, EOLParams};
 
     use super::*;
     use cosmwasm_std::to_json_vec;
@@ -194,12 +292,99 @@ mod tests {
         }
     }
 
+    #[rstest]
+    #[case("1.8", Err(CompositeAuthenticatorError::CompositeTooWide { max_width: 8 }))]
+    #[case(
+        "1.18446744073709551615",
+        Err(CompositeAuthenticatorError::CompositeTooWide { max_width: 8 })
+    )]
+    #[case("1.7.7.7.7", Err(CompositeAuthenticatorError::CompositeTooDeep { max_depth: 3 }))]
+    #[case("1.7.7.7", Ok(CompositeId { root: 1, path: vec![7, 7, 7] }))]
+    fn test_composite_id_parse_limits(
+        #[case] composite_id: &str,
+        #[case] expected: Result<CompositeId, CompositeAuthenticatorError>,
+    ) {
+        let limits = CompositeLimits {
+            max_depth: 3,
+            max_width: 8,
+        };
+        assert_eq!(CompositeId::parse(composite_id, &limits), expected);
+    }
+
+    #[test]
+    fn test_child_authenticator_data_limits() {
+        let sub_auths = |width: usize, config: Vec<u8>| {
+            to_json_vec(
+                &(0..width)
+                    .map(|_| SubAuthenticatorData {
+                        r#type: "AllOf".to_string(),
+                        config: config.clone(),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap()
+        };
+        let limits = CompositeLimits {
+            max_depth: 2,
+            max_width: 2,
+        };
+
+        let account_auth = AccountAuthenticator {
+            config: sub_auths(3, b"{}".to_vec()),
+            id: 1,
+            r#type: "AllOf".to_string(),
+        };
+        assert_eq!(
+            account_auth
+                .child_authenticator_data::<CosmwasmAuthenticatorData>(&[0], &limits)
+                .unwrap_err(),
+            CompositeAuthenticatorError::CompositeTooWide { max_width: 2 }
+        );
+
+        // too wide is caught before the sub-authenticators get decoded
+        let account_auth = AccountAuthenticator {
+            config: b"[0, 1, 2]".to_vec(),
+            id: 1,
+            r#type: "AllOf".to_string(),
+        };
+        assert_eq!(
+            account_auth
+                .child_authenticator_data::<CosmwasmAuthenticatorData>(&[0], &limits)
+                .unwrap_err(),
+            CompositeAuthenticatorError::CompositeTooWide { max_width: 2 }
+        );
+
+        let account_auth = AccountAuthenticator {
+            config: sub_auths(1, sub_auths(1, sub_auths(1, b"{}".to_vec()))),
+            id: 1,
+            r#type: "AllOf".to_string(),
+        };
+        assert_eq!(
+            account_auth
+                .child_authenticator_data::<CosmwasmAuthenticatorData>(&[0, 0, 0], &limits)
+                .unwrap_err(),
+            CompositeAuthenticatorError::CompositeTooDeep { max_depth: 2 }
+        );
+    }
+
+    #[rstest]
+    #[case("1", "1", true)]
+    #[case("1", "1.0.2", true)]
+    #[case("1.2", "1.2.0", true)]
+    #[case("1.2", "1.20", false)]
+    #[case("1.2", "1", false)]
+    #[case("1", "10", false)]
+    fn test_composite_id_contains(#[case] id: &str, #[case] other: &str, #[case] expected: bool) {
+        let id = CompositeId::from_str(id).unwrap();
+        let other = CompositeId::from_str(other).unwrap();
+        assert_eq!(id.contains(&other), expected);
+    }
+
     #[test]
     fn test_child_authenticator_data() {
-        let params = SpendLimitParams {
-            limit: 1000000u128.into(),
-            reset_period: Period::Day,
-            time_limit: None,
+        let params = EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            ..EOLParams::default()
         };
         // no depth
         let target_data = CosmwasmAuthenticatorData {
@@ -212,9 +397,11 @@ mod tests {
             r#type: "AllOf".to_string(),
         };
 
-        let result: Result<CosmwasmAuthenticatorData, CompositeAuthenticatorError> =
-            account_auth.child_authenticator_data(&[]);
-        assert_eq!(result.unwrap(), target_data);
+        let result: Result<
+            ChildAuthenticator<CosmwasmAuthenticatorData>,
+            CompositeAuthenticatorError,
+        > = account_auth.child_authenticator_data(&[], &CompositeLimits::default());
+        assert_eq!(result.unwrap().data, target_data);
 
         // depth 1
 
@@ -238,9 +425,11 @@ mod tests {
             r#type: "AllOf".to_string(),
         };
 
-        let result: Result<CosmwasmAuthenticatorData, CompositeAuthenticatorError> =
-            account_auth.child_authenticator_data(&[1]);
-        assert_eq!(result.unwrap(), target_data);
+        let result: Result<
+            ChildAuthenticator<CosmwasmAuthenticatorData>,
+            CompositeAuthenticatorError,
+        > = account_auth.child_authenticator_data(&[1], &CompositeLimits::default());
+        assert_eq!(result.unwrap().data, target_data);
 
         // more depth
 
@@ -279,19 +468,29 @@ mod tests {
             r#type: "AllOf".to_string(),
         };
 
-        let result: Result<CosmwasmAuthenticatorData, CompositeAuthenticatorError> =
-            account_auth.clone().child_authenticator_data(&[0, 1]);
-        assert_eq!(result.unwrap(), target_data);
-
-        let result: Result<CosmwasmAuthenticatorData, CompositeAuthenticatorError> =
-            account_auth.clone().child_authenticator_data(&[0, 2]);
+        let result: Result<
+            ChildAuthenticator<CosmwasmAuthenticatorData>,
+            CompositeAuthenticatorError,
+        > = account_auth
+            .clone()
+            .child_authenticator_data(&[0, 1], &CompositeLimits::default());
+        assert_eq!(result.unwrap().data, target_data);
+
+        let result: Result<
+            ChildAuthenticator<CosmwasmAuthenticatorData>,
+            CompositeAuthenticatorError,
+        > = account_auth
+            .clone()
+            .child_authenticator_data(&[0, 2], &CompositeLimits::default());
         assert_eq!(
             result.unwrap_err(),
             CompositeAuthenticatorError::invalid_composite_id("1.0.2")
         );
 
-        let result: Result<CosmwasmAuthenticatorData, CompositeAuthenticatorError> =
-            account_auth.child_authenticator_data(&[10]);
+        let result: Result<
+            ChildAuthenticator<CosmwasmAuthenticatorData>,
+            CompositeAuthenticatorError,
+        > = account_auth.child_authenticator_data(&[10], &CompositeLimits::default());
         assert_eq!(
             result.unwrap_err(),
             CompositeAuthenticatorError::invalid_composite_id("1.10")
@@ -306,8 +505,15 @@ mod tests {
             r#type: "AllOf".to_string(),
         };
 
-        let cosmwasm_auth_data: CosmwasmAuthenticatorData =
-            account_auth.clone().child_authenticator_data(&[1]).unwrap();
+        let ChildAuthenticator::<CosmwasmAuthenticatorData> {
+            r#type,
+            data: cosmwasm_auth_data,
+        } = account_auth
+            .clone()
+            .child_authenticator_data(&[1], &CompositeLimits::default())
+            .unwrap();
+
+        assert_eq!(r#type, COSMWASM_AUTHENTICATOR);
 
         assert_eq!(cosmwasm_auth_data, CosmwasmAuthenticatorData {
             contract: "osmo16el87tfzcqwaeqra7e5y3hxdxg2j5g8ypfd0pmukuxg822lh5rcqhugu7d".to_string(),
@@ -2,6 +2,7 @@ use cosmwasm_std::{Addr, StdError};
 use thiserror::Error;
 
 use super::composite::CompositeAuthenticatorError;
+use crate::pause::PauseScope;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum AuthenticatorError {
@@ -25,6 +26,15 @@ pub enum AuthenticatorError {
         account: Addr,
         authenticator_id: String,
     },
+
+    #[error("Params don't match the ones registered for account {account} and authenticator id {authenticator_id}")]
+    ParamsMismatch {
+        account: Addr,
+        authenticator_id: String,
+    },
+
+    #[error("{scope} is paused")]
+    Paused { scope: PauseScope },
 }
 
 impl AuthenticatorError {
@@ -38,4 +48,11 @@ impl AuthenticatorError {
             authenticator_id: authenticator_id.to_string(),
         }
     }
+
+    pub fn params_mismatch(account: Addr, authenticator_id: &str) -> Self {
+        Self::ParamsMismatch {
+            account,
+            authenticator_id: authenticator_id.to_string(),
+        }
+    }
 }
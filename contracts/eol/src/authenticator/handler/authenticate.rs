@@ -1,339 +1,903 @@
-use super::validate_and_parse_params;
-use crate::state::EOLS;
+use std::str::FromStr;
+
+use super::verify_and_parse_params;
+use crate::authenticator::{AuthenticatorError, CompositeId};
+use crate::eol::{
+    error::EOLError, policy::MsgClass, takeover::ensure_takeover_shape, EOLParams, Session, Signer,
+    EOL,
+};
+use crate::msg::ExecuteMsg;
+use crate::pause::{ensure_not_paused, PauseScope};
+use crate::state::{EOLS, SESSIONS};
 use crate::ContractError;
-use cosmwasm_std::{DepsMut, Env, Response, StdError};
+use cosmwasm_std::{ensure, from_json, Addr, Api, DepsMut, Env, Response};
 use cw_authenticator::AuthenticationRequest;
+use osmosis_std::types::cosmwasm::wasm::v1::MsgExecuteContract;
+use osmosis_std::types::osmosis::smartaccount::v1beta1::SmartaccountQuerier;
 
 pub fn authenticate(
     deps: DepsMut,
     env: Env,
     auth_request: AuthenticationRequest,
 ) -> Result<Response, ContractError> {
-    let params = validate_and_parse_params(auth_request.authenticator_params)?;
-
-    // temp disallow multisigs
-    // assume auth_request.signature_data.signers outputs just one signer and the
-    // signer is the one actually signing the tx and not the account the tx is signed for
-    if auth_request.signature_data.signers.len() != 1 {
-        return Err(ContractError::Unauthorized {});
-    }
-    // handles the case when account is being used by the original user
-    if auth_request.signature_data.signers.contains(&auth_request.account) {
-        return Ok(Response::new().add_attribute("action", "authenticate"));
-    }
-
     let key = (
         &auth_request.account,
         auth_request.authenticator_id.as_str(),
     );
-    let mut eol = EOLS.load(deps.storage, key)?;
-    let _ = eol.ensure_out_of_limit(env.block.time).unwrap();
+    let params = verify_and_parse_params(
+        &deps.querier,
+        &env.contract.address,
+        key.0,
+        key.1,
+        auth_request.authenticator_params.clone(),
+    )?;
+
+    let sign_doc = auth_request.sign_mode_tx_data.sign_mode_direct.as_slice();
+    let signature = auth_request.signature.as_slice();
 
-    Ok(Response::new().add_attribute("action", "authenticate"))
+    // handles the case when account is being used by the original user, the signers
+    // of the tx are always the account itself so only the owner's key can vouch for it.
+    // Once a beneficiary took the account over, the owner's key is locked out as well.
+    if params.signed_by_owner(deps.api, sign_doc, signature) {
+        EOLS.load(deps.storage, key)?.ensure_not_claimed()?;
+        SESSIONS.save(
+            deps.storage,
+            (key.0, key.1, auth_request.msg_index),
+            &Session::new(Signer::Owner, &env.block).with_simulate(auth_request.simulate),
+        )?;
+
+        return Ok(Response::new()
+            .add_attribute("action", "authenticate")
+            .add_attribute("signer", "owner"));
+    }
+
+    // handles the case when the tx is signed by one of the beneficiaries' keys
+    if let Some(index) = params.signing_beneficiary(deps.api, sign_doc, signature) {
+        let eol = EOLS.load(deps.storage, key)?;
+        return authenticate_beneficiary(
+            deps,
+            &env,
+            &params,
+            &eol,
+            &auth_request,
+            Signer::Beneficiary { index },
+        );
+    }
+
+    // handles the case when the tx is signed by a committed beneficiary, either already
+    // revealed or revealing itself inline as part of this transaction
+    let eol = EOLS.load(deps.storage, key)?;
+    let address = match eol.signing_beneficiary(deps.api, sign_doc, signature) {
+        Some(address) => Some(address.clone()),
+        None => revealed_beneficiary(deps.api, &env, &auth_request, &eol)?,
+    };
+    if let Some(address) = address {
+        return authenticate_beneficiary(
+            deps,
+            &env,
+            &params,
+            &eol,
+            &auth_request,
+            Signer::CommittedBeneficiary { address },
+        );
+    }
+
+    Err(ContractError::Unauthorized {})
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//     use crate::fee::UntrackedSpentFee;
-//     use crate::period::Period;
-//     use crate::price::{track_denom, PriceResolutionConfig};
-//     use crate::spend_limit::{SpendLimitError, Spending};
-//     use crate::spend_limit::{SpendLimitParams, TimeLimit};
-//     use crate::test_helper::mock_stargate_querier::{
-//         arithmetic_twap_to_now_query_handler, mock_dependencies_with_stargate_querier,
-//     };
-//
-//     use crate::state::PRICE_INFOS;
-//     use cosmwasm_std::{
-//         testing::{mock_dependencies_with_balances, mock_env},
-//         to_json_binary, Addr, Binary, Coin, ContractResult, Timestamp,
-//     };
-//     use cw_authenticator::{Any, SignModeTxData, SignatureData, TxData};
-//     use osmosis_std::types::osmosis::poolmanager::v1beta1::SwapAmountInRoute;
-//     use osmosis_std::types::osmosis::twap::v1beta1::ArithmeticTwapToNowResponse;
-//     use rstest::rstest;
-//
-//     #[rstest]
-//     #[case::no_time_limit(0, None, true)]
-//     #[case::no_time_limit(1_771_797_419_879_305_533, None, true)]
-//     #[case::no_time_limit(u64::MAX, None, true)]
-//     #[case::within_time_limit(1_771_797_419_879_305_533, Some((Some(current), current + 1)), true)]
-//     #[case::within_time_limit(1_771_797_419_879_305_533, Some((Some(current), current)), true)]
-//     #[case::within_time_limit(1_771_797_419_879_305_533, Some((None, current)), true)]
-//     #[case::not_within_time_limit(1_771_797_419_879_305_533, Some((Some(current), current - 1)
-//     ), false)]
-//     #[case::not_within_time_limit(1_771_797_419_879_305_533, Some((Some(current + 1), current)
-//     ), false)]
-//     #[case::not_within_time_limit(1_771_797_419_879_305_533, Some((None, current - 1)), false)]
-//     fn test_authenticate_time_limit(
-//         #[case] current: u64,
-//         #[case] time_limit: Option<(Option<u64>, u64)>,
-//         #[case] expected: bool,
-//     ) {
-//         // Setup the environment
-//         let mut deps = mock_dependencies_with_balances(&[("addr", &[])]);
-//
-//         let key = (&Addr::unchecked("addr"), "2");
-//
-//         SPENDINGS
-//             .save(&mut deps.storage, key, &Spending::default())
-//             .unwrap();
-//
-//         PRICE_RESOLUTION_CONFIG
-//             .save(
-//                 deps.as_mut().storage,
-//                 &PriceResolutionConfig {
-//                     quote_denom: "uusdc".to_string(),
-//                     staleness_threshold: 3_600_000_000_000u64.into(), // 1h
-//                     twap_duration: 3_600_000_000_000u64.into(),       // 1h
-//                 },
-//             )
-//             .unwrap();
-//
-//         let time_limit = time_limit.map(|(start, end)| TimeLimit {
-//             start: start.map(Timestamp::from_nanos),
-//             end: Timestamp::from_nanos(end),
-//         });
-//
-//         let request = AuthenticationRequest {
-//             authenticator_id: "2".to_string(),
-//             account: Addr::unchecked("addr"),
-//             fee_payer: Addr::unchecked("addr"),
-//             fee_granter: None,
-//             fee: vec![],
-//             authenticator_params: Some(
-//                 to_json_binary(&SpendLimitParams {
-//                     limit: 1000u128.into(),
-//                     reset_period: Period::Day,
-//                     time_limit: time_limit.clone(),
-//                 })
-//                     .unwrap(),
-//             ),
-//             msg: Any {
-//                 type_url: "".to_string(),
-//                 value: Binary::default(),
-//             },
-//             msg_index: 0,
-//             signature: Binary::default(),
-//             sign_mode_tx_data: SignModeTxData {
-//                 sign_mode_direct: Binary::default(),
-//                 sign_mode_textual: None,
-//             },
-//             tx_data: TxData {
-//                 chain_id: "osmosis-1".to_string(),
-//                 account_number: 0,
-//                 sequence: 0,
-//                 timeout_height: 0,
-//                 msgs: vec![],
-//                 memo: "".to_string(),
-//             },
-//             signature_data: SignatureData {
-//                 signers: vec![],
-//                 signatures: vec![],
-//             },
-//             simulate: false,
-//         };
-//
-//         let mut env = mock_env();
-//
-//         env.block.time = Timestamp::from_nanos(current);
-//
-//         let response = authenticate(deps.as_mut(), env.clone(), request);
-//
-//         if expected {
-//             response.expect("expected authenticated");
-//         } else {
-//             let TimeLimit { start, end } = time_limit.unwrap();
-//             assert_eq!(
-//                 response.unwrap_err(),
-//                 ContractError::NotWithinTimeLimit {
-//                     current: env.block.time,
-//                     start,
-//                     end,
-//                 }
-//             );
-//         }
-//     }
-//
-//     #[rstest]
-//     #[case::no_fee_spent("account", None, vec![], vec![], Ok(()))]
-//     #[case::fee_spent_to_the_limit("account", None, vec![Coin::new(1_000_000_000, "uusdc")], vec![], Ok(())
-//     )]
-//     #[case::fee_spent_to_the_limit("account", None, vec![Coin::new(666_666_666, "uosmo")], vec![], Ok(())
-//     )]
-//     #[case::fee_spent_to_the_limit_with_untracked_denoms("account", None,
-//         vec![Coin::new(333_333_333, "uosmo"), Coin::new(500_000_000, "untracked1")],
-//         vec![Coin::new(500_000_000, "uusdc"), Coin::new(500_000_000, "untracked2")],
-//         Ok(()))
-//     ]
-//     #[case::fee_spent_over_the_limit("account", None,
-//         vec![Coin::new(1_000_000_001, "uusdc")],
-//         vec![],
-//         Err(SpendLimitError::overspend(1_000_000_000, 1_000_000_001).into()))
-//     ]
-//     #[case::fee_spent_over_the_limit("account", None,
-//         vec![Coin::new(666_666_668, "uosmo")],
-//         vec![],
-//         Err(SpendLimitError::overspend(1_000_000_000, 1_000_000_002).into()))
-//     ]
-//     #[case::fee_spent_over_the_limit("account", None,
-//         vec![Coin::new(1_000_000_001, "uusdc")],
-//         vec![],
-//         Err(SpendLimitError::overspend(1_000_000_000, 1_000_000_001).into()))
-//     ]
-//     #[case::fee_spent_over_the_limit("account", None,
-//         vec![Coin::new(333_333_333, "uosmo"), Coin::new(500_000_001, "uusdc")],
-//         vec![],
-//         Err(SpendLimitError::overspend(1_000_000_000, 1_000_000_001).into()))
-//     ]
-//     #[case::fee_spent_over_the_limit("account", None,
-//         vec![Coin::new(333_333_333, "uosmo")],
-//         vec![Coin::new(500_000_001, "uusdc")],
-//         Err(SpendLimitError::overspend(1_000_000_000, 1_000_000_001).into()))
-//     ]
-//     #[case::fee_spent_over_the_limit_by_fee_grant("account", Some("granter"),
-//         vec![Coin::new(1_000_000_001, "uusdc")],
-//         vec![],
-//         Ok(()))
-//     ]
-//     #[case::fee_spent_over_the_limit_by_fee_grant_and_untracked_fee("account", Some("granter"),
-//         vec![Coin::new(1_000_000_001, "uusdc")],
-//         vec![Coin::new(1_000_000_001, "uusdc")],
-//         Err(SpendLimitError::overspend(1_000_000_000, 1_000_000_001).into()))
-//     ]
-//     #[case::fee_spent_over_the_limit_by_fee_grant_and_untracked_fee("non_account", Some("granter"),
-//         vec![Coin::new(1_000_000_001, "uusdc")],
-//         vec![Coin::new(1_000_000_001, "uusdc")],
-//         Err(SpendLimitError::overspend(1_000_000_000, 1_000_000_001).into()))
-//     ]
-//     #[case::fee_spent_over_the_limit_with_granter_as_account("account", Some("account"),
-//         vec![Coin::new(1_000_000_001, "uusdc")],
-//         vec![],
-//         Err(SpendLimitError::overspend(1_000_000_000, 1_000_000_001).into()))
-//     ]
-//     #[case::fee_spent_over_the_limit_with_granter_as_account("non_account", Some("account"),
-//         vec![Coin::new(1_000_000_001, "uusdc")],
-//         vec![],
-//         Err(SpendLimitError::overspend(1_000_000_000, 1_000_000_001).into()))
-//     ]
-//     #[case::fee_spent_over_the_limit_by_non_account_fee_payer("non_account", None,
-//         vec![Coin::new(1_000_000_001, "uusdc")],
-//         vec![],
-//         Ok(()))
-//     ]
-//     #[case::fee_spent_over_the_limit_by_non_account_fee_payer_and_untracked_fee("non_account", None,
-//         vec![Coin::new(1_000_000_001, "uusdc")],
-//         vec![Coin::new(1_000_000_001, "uusdc")],
-//         Err(SpendLimitError::overspend(1_000_000_000, 1_000_000_001).into()))
-//     ]
-//     fn test_authenticate_fee_spent(
-//         #[case] fee_payer: &str,
-//         #[case] fee_granter: Option<&str>,
-//         #[case] fee: Vec<Coin>,
-//         #[case] untracked_spent_fee: Vec<Coin>,
-//         #[case] result: Result<(), ContractError>,
-//     ) {
-//         // Setup the environment
-//
-//         let mut deps = mock_dependencies_with_stargate_querier(
-//             &[],
-//             arithmetic_twap_to_now_query_handler(Box::new(|req| {
-//                 let base_asset = req.base_asset.as_str();
-//                 let quote_asset = req.quote_asset.as_str();
-//
-//                 let arithmetic_twap = match (base_asset, quote_asset) {
-//                     ("uosmo", "uusdc") => "1.5",
-//                     _ => return ContractResult::Err("Price not found".to_string()),
-//                 }
-//                     .to_string();
-//
-//                 ContractResult::Ok(ArithmeticTwapToNowResponse { arithmetic_twap })
-//             })),
-//         );
-//
-//         let key = (&Addr::unchecked("account"), "2");
-//
-//         SPENDINGS
-//             .save(&mut deps.storage, key, &Spending::default())
-//             .unwrap();
-//
-//         let conf = PriceResolutionConfig {
-//             quote_denom: "uusdc".to_string(),
-//             staleness_threshold: 3_600_000_000_000u64.into(), // 1h
-//             twap_duration: 3_600_000_000_000u64.into(),       // 1h
-//         };
-//         PRICE_RESOLUTION_CONFIG
-//             .save(deps.as_mut().storage, &conf)
-//             .unwrap();
-//
-//         track_denom(
-//             &PRICE_INFOS,
-//             deps.as_mut(),
-//             &conf,
-//             "uosmo",
-//             mock_env().block.time,
-//             vec![SwapAmountInRoute {
-//                 pool_id: 666,
-//                 token_out_denom: "uusdc".to_string(),
-//             }],
-//         )
-//             .unwrap();
-//
-//         if !untracked_spent_fee.is_empty() {
-//             UNTRACKED_SPENT_FEES
-//                 .save(
-//                     &mut deps.storage,
-//                     key,
-//                     &UntrackedSpentFee {
-//                         fee: untracked_spent_fee,
-//                         updated_at: mock_env().block.time,
-//                     },
-//                 )
-//                 .unwrap();
-//         }
-//
-//         let request = AuthenticationRequest {
-//             authenticator_id: "2".to_string(),
-//             account: Addr::unchecked("account"),
-//             fee_payer: Addr::unchecked(fee_payer),
-//             fee_granter: fee_granter.map(Addr::unchecked),
-//             fee,
-//             authenticator_params: Some(
-//                 to_json_binary(&SpendLimitParams {
-//                     limit: 1_000_000_000u128.into(),
-//                     reset_period: Period::Day,
-//                     time_limit: None,
-//                 })
-//                     .unwrap(),
-//             ),
-//             msg: Any {
-//                 type_url: "".to_string(),
-//                 value: Binary::default(),
-//             },
-//             msg_index: 0,
-//             signature: Binary::default(),
-//             sign_mode_tx_data: SignModeTxData {
-//                 sign_mode_direct: Binary::default(),
-//                 sign_mode_textual: None,
-//             },
-//             tx_data: TxData {
-//                 chain_id: "osmosis-1".to_string(),
-//                 account_number: 0,
-//                 sequence: 0,
-//                 timeout_height: 0,
-//                 msgs: vec![],
-//                 memo: "".to_string(),
-//             },
-//             signature_data: SignatureData {
-//                 signers: vec![],
-//                 signatures: vec![],
-//             },
-//             simulate: false,
-//         };
-//
-//         let response = authenticate(deps.as_mut(), mock_env(), request).map(|_| ());
-//
-//         assert_eq!(response, result);
-//     }
-// }
+/// Beneficiaries may only use the account once it went dormant,
+/// and only for the messages their policy allows
+fn authenticate_beneficiary(
+    deps: DepsMut,
+    env: &Env,
+    params: &EOLParams,
+    eol: &EOL,
+    auth_request: &AuthenticationRequest,
+    signer: Signer,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage, PauseScope::Beneficiaries)?;
+    eol.ensure_not_claimed()?;
+    eol.ensure_out_of_limit(env.block.time)?;
+    params.ensure_fee_allowed(eol, auth_request, env.block.time)?;
+
+    let takeover_key = match &signer {
+        Signer::Beneficiary { index } => params.takeover_key(*index),
+        _ => None,
+    };
+
+    // authenticator management is only sanctioned as part of a takeover,
+    // which must be exactly the expected transaction
+    let takeover = match takeover_key {
+        Some(pubkey)
+            if MsgClass::of(&auth_request.msg.type_url) == MsgClass::AuthenticatorManagement =>
+        {
+            let existing = SmartaccountQuerier::new(&deps.querier)
+                .get_authenticators(auth_request.account.to_string())?
+                .account_authenticators
+                .into_iter()
+                .map(|authenticator| authenticator.id)
+                .collect::<Vec<_>>();
+            let id = CompositeId::from_str(&auth_request.authenticator_id)
+                .map_err(AuthenticatorError::from)?;
+            // the authenticator is kept to track the claim, nested in a composite
+            // authenticator it would keep the other sub-authenticators along with it
+            ensure!(
+                id.path.is_empty(),
+                EOLError::invalid_takeover(
+                    "authenticator must not be nested in a composite authenticator"
+                )
+            );
+
+            ensure_takeover_shape(
+                &auth_request.tx_data.msgs,
+                &auth_request.account,
+                pubkey,
+                &existing,
+                id.root,
+            )?;
+            true
+        }
+        _ => {
+            params.ensure_allowed_for_beneficiary(&auth_request.msg)?;
+            params.ensure_contract_call_allowed(&auth_request.msg, &env.contract.address)?;
+            params.ensure_staking_allowed(&auth_request.msg)?;
+            false
+        }
+    };
+
+    SESSIONS.save(
+        deps.storage,
+        (
+            &auth_request.account,
+            auth_request.authenticator_id.as_str(),
+            auth_request.msg_index,
+        ),
+        &Session::new(signer, &env.block)
+            .with_takeover(takeover)
+            .with_simulate(auth_request.simulate),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "authenticate")
+        .add_attribute("signer", "beneficiary"))
+}
+
+/// Address of the committed beneficiary revealing itself through a `RevealBeneficiary`
+/// execution against this contract in the transaction, which must open one of the pending
+/// commitments with a key that derives the address and produced the signature
+fn revealed_beneficiary(
+    api: &dyn Api,
+    env: &Env,
+    auth_request: &AuthenticationRequest,
+    eol: &EOL,
+) -> Result<Option<Addr>, ContractError> {
+    for msg in auth_request.tx_data.msgs.iter() {
+        if msg.type_url != MsgExecuteContract::TYPE_URL {
+            continue;
+        }
+
+        let execute = MsgExecuteContract::try_from(msg.value.clone())?;
+        if execute.contract != env.contract.address.as_str() {
+            continue;
+        }
+
+        if let Ok(ExecuteMsg::RevealBeneficiary {
+            account,
+            authenticator_id,
+            address,
+            pubkey,
+            salt,
+        }) = from_json(&execute.msg)
+        {
+            if account == auth_request.account.as_str()
+                && authenticator_id == auth_request.authenticator_id
+                && eol.is_committed_beneficiary(&address, salt.as_slice())
+                && pubkey.derives_address(&address)
+                && pubkey.verify(
+                    api,
+                    auth_request.sign_mode_tx_data.sign_mode_direct.as_slice(),
+                    auth_request.signature.as_slice(),
+                )
+            {
+                return Ok(Some(Addr::unchecked(address)));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authenticator::{
+        CosmwasmAuthenticatorData, SubAuthenticatorData, COSMWASM_AUTHENTICATOR,
+    };
+    use crate::eol::{error::EOLError, params::beneficiary_commitment, BeneficiaryKey, EOLParams};
+    use crate::eol::{
+        takeover::SIGNATURE_VERIFICATION, Duration, FeeBudget, FeePolicy, FeeSpending,
+    };
+    use crate::pause::PauseStatus;
+    use crate::state::PAUSE_STATUS;
+    use crate::test_helper::mock_stargate_querier::{
+        chain_query_handlers, get_authenticator_query_handler, get_authenticators_query_handler,
+        mock_dependencies_with_registered_params, mock_dependencies_with_stargate_querier,
+        registered_params_query_handler, MockStargateQuerier,
+    };
+    use cosmwasm_std::{coins, ContractResult, OwnedDeps};
+    use cosmwasm_std::{
+        testing::{mock_env, MockApi, MockStorage, MOCK_CONTRACT_ADDR},
+        to_json_binary, to_json_vec, Binary, HexBinary,
+    };
+    use cw_authenticator::{Any, SignModeTxData, SignatureData, TxData};
+    use osmosis_std::types::osmosis::smartaccount::v1beta1::{
+        AccountAuthenticator, GetAuthenticatorResponse, GetAuthenticatorsResponse,
+        MsgAddAuthenticator, MsgRemoveAuthenticator,
+    };
+    use rstest::rstest;
+
+    // ed25519 test vector taken from cosmwasm-crypto
+    const SIGN_DOC: &str = "72";
+    const SIGNATURE: &str = "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00";
+    const PUBKEY: &str = "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
+
+    // secp256k1 test vector taken from cosmwasm-crypto, signed by the owner
+    const OWNER_SIGN_DOC: &str = "5c868fedb8026979ebd26f1ba07c27eedf4ff6d10443505a96ecaf21ba8c4f0937b3cd23ffdc3dd429d4cd1905fb8dbcceeff1350020e18b58d2ba70887baa3a9b783ad30d3fbf210331cdd7df8d77defa398cdacdfc2e359c7ba4cae46bb74401deb417f8b912a1aa966aeeba9c39c7dd22479ae2b30719dca2f2206c5eb4b7";
+    const OWNER_SIGNATURE: &str = "207082eb2c3dfa0b454e0906051270ba4074ac93760ba9e7110cd9471475111151eb0dbbc9920e72146fb564f99d039802bf6ef2561446eb126ef364d21ee9c4";
+    const OWNER_PUBKEY: &str = "03051c1ee2190ecfb174bfe4f90763f2b4ff7517b70a2aec1876ebcfd644c4633f";
+
+    fn hex(s: &str) -> Binary {
+        HexBinary::from_hex(s).unwrap().to_vec().into()
+    }
+
+    fn params() -> EOLParams {
+        EOLParams {
+            owner_key: Some(BeneficiaryKey::Secp256k1(hex(OWNER_PUBKEY))),
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            beneficiaries: vec![BeneficiaryKey::Ed25519(hex(PUBKEY))],
+            ..EOLParams::default()
+        }
+    }
+
+    /// Dependencies with `params` registered as the account's authenticator config
+    fn mock_dependencies_with_params(
+        params: &EOLParams,
+    ) -> OwnedDeps<MockStorage, MockApi, MockStargateQuerier> {
+        mock_dependencies_with_registered_params(&[], &to_json_binary(params).unwrap())
+    }
+
+    fn auth_request(signer: &str, signature: Binary) -> AuthenticationRequest {
+        AuthenticationRequest {
+            authenticator_id: "2".to_string(),
+            account: Addr::unchecked("account"),
+            fee_payer: Addr::unchecked("account"),
+            fee_granter: None,
+            fee: vec![],
+            authenticator_params: Some(to_json_binary(&params()).unwrap()),
+            msg: Any {
+                type_url: "".to_string(),
+                value: Binary::default(),
+            },
+            msg_index: 0,
+            signature,
+            sign_mode_tx_data: SignModeTxData {
+                sign_mode_direct: hex(SIGN_DOC),
+                sign_mode_textual: None,
+            },
+            tx_data: TxData {
+                chain_id: "osmosis-1".to_string(),
+                account_number: 0,
+                sequence: 0,
+                timeout_height: 0,
+                msgs: vec![],
+                memo: "".to_string(),
+            },
+            signature_data: SignatureData {
+                signers: vec![Addr::unchecked(signer)],
+                signatures: vec![],
+            },
+            simulate: false,
+        }
+    }
+
+    fn owner_request() -> AuthenticationRequest {
+        let mut request = auth_request("account", hex(OWNER_SIGNATURE));
+        request.sign_mode_tx_data.sign_mode_direct = hex(OWNER_SIGN_DOC);
+        request
+    }
+
+    #[test]
+    fn test_authenticate_owner() {
+        let mut deps = mock_dependencies_with_params(&params());
+        let env = mock_env();
+        let key = (&Addr::unchecked("account"), "2");
+        EOLS.save(
+            deps.as_mut().storage,
+            key,
+            &EOL::new(Duration::from_seconds(100).unwrap(), env.block.time),
+        )
+        .unwrap();
+
+        authenticate(deps.as_mut(), env.clone(), owner_request()).unwrap();
+
+        let session = SESSIONS
+            .load(deps.as_ref().storage, (&Addr::unchecked("account"), "2", 0))
+            .unwrap();
+        assert_eq!(session, Session::new(Signer::Owner, &env.block));
+
+        // locked out once a beneficiary took the account over
+        let mut eol = EOLS.load(deps.as_ref().storage, key).unwrap();
+        eol.claim(env.block.time);
+        EOLS.save(deps.as_mut().storage, key, &eol).unwrap();
+        assert_eq!(
+            authenticate(deps.as_mut(), env.clone(), owner_request()).unwrap_err(),
+            EOLError::AlreadyClaimed {
+                claimed_at: env.block.time
+            }
+            .into()
+        );
+
+        // the account being the signer doesn't vouch for the owner
+        assert_eq!(
+            authenticate(
+                deps.as_mut(),
+                env.clone(),
+                auth_request("account", Binary::default())
+            )
+            .unwrap_err(),
+            ContractError::Unauthorized {}
+        );
+
+        // no owner key to check the signature against
+        let no_owner_key = to_json_binary(&EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            ..EOLParams::default()
+        })
+        .unwrap();
+        deps.querier
+            .update_stargate_handler(registered_params_query_handler(
+                MOCK_CONTRACT_ADDR,
+                &no_owner_key,
+            ));
+        let mut request = owner_request();
+        request.authenticator_params = Some(no_owner_key);
+        assert_eq!(
+            authenticate(deps.as_mut(), env, request).unwrap_err(),
+            ContractError::Unauthorized {}
+        );
+    }
+
+    #[test]
+    fn test_authenticate_beneficiary() {
+        let mut deps = mock_dependencies_with_params(&params());
+        let mut env = mock_env();
+        let key = (&Addr::unchecked("account"), "2");
+        EOLS.save(
+            deps.as_mut().storage,
+            key,
+            &EOL::new(Duration::from_seconds(100).unwrap(), env.block.time),
+        )
+        .unwrap();
+
+        // account is still alive
+        assert_eq!(
+            authenticate(
+                deps.as_mut(),
+                env.clone(),
+                auth_request("beneficiary", hex(SIGNATURE))
+            )
+            .unwrap_err(),
+            EOLError::TimeInBoundsError {
+                out_of_bounds_limit: env.block.time.plus_seconds(100)
+            }
+            .into()
+        );
+        assert!(!SESSIONS.has(deps.as_ref().storage, (&Addr::unchecked("account"), "2", 0)));
+
+        // account went dormant
+        env.block.time = env.block.time.plus_seconds(101);
+        authenticate(
+            deps.as_mut(),
+            env.clone(),
+            auth_request("beneficiary", hex(SIGNATURE)),
+        )
+        .unwrap();
+
+        let session = SESSIONS
+            .load(deps.as_ref().storage, (&Addr::unchecked("account"), "2", 0))
+            .unwrap();
+        assert_eq!(
+            session,
+            Session::new(Signer::Beneficiary { index: 0 }, &env.block)
+        );
+
+        // beneficiaries can't reconfigure the account's authenticators
+        let mut request = auth_request("beneficiary", hex(SIGNATURE));
+        request.msg = Any {
+            type_url: MsgRemoveAuthenticator::TYPE_URL.to_string(),
+            value: MsgRemoveAuthenticator {
+                sender: "account".to_string(),
+                id: 2,
+            }
+            .into(),
+        };
+        assert_eq!(
+            authenticate(deps.as_mut(), env, request).unwrap_err(),
+            EOLError::message_not_allowed(MsgRemoveAuthenticator::TYPE_URL).into()
+        );
+    }
+
+    #[test]
+    fn test_authenticate_paused() {
+        let mut deps = mock_dependencies_with_params(&params());
+        let mut env = mock_env();
+        let key = (&Addr::unchecked("account"), "2");
+        EOLS.save(
+            deps.as_mut().storage,
+            key,
+            &EOL::new(Duration::from_seconds(100).unwrap(), env.block.time),
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(101);
+
+        let mut status = PauseStatus::default();
+        status.set(PauseScope::Beneficiaries, true);
+        PAUSE_STATUS.save(deps.as_mut().storage, &status).unwrap();
+
+        // beneficiaries are stopped
+        assert_eq!(
+            authenticate(
+                deps.as_mut(),
+                env.clone(),
+                auth_request("beneficiary", hex(SIGNATURE))
+            )
+            .unwrap_err(),
+            AuthenticatorError::Paused {
+                scope: PauseScope::Beneficiaries
+            }
+            .into()
+        );
+        assert!(!SESSIONS.has(deps.as_ref().storage, (&Addr::unchecked("account"), "2", 0)));
+
+        // while the owner is not
+        authenticate(deps.as_mut(), env.clone(), owner_request()).unwrap();
+
+        // and beneficiaries are back once unpaused
+        status.set(PauseScope::Beneficiaries, false);
+        PAUSE_STATUS.save(deps.as_mut().storage, &status).unwrap();
+        authenticate(
+            deps.as_mut(),
+            env,
+            auth_request("beneficiary", hex(SIGNATURE)),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_authenticate_committed_beneficiary() {
+        // addresses of the ed25519 key and of the secp256k1 key behind the owner vector
+        let heir1 = "osmo188m3859xgsjn7pzjjssmnagmnvyf08ggtmtd6x";
+        let heir2 = "osmo14g0cww50zdysxrxfpjvsrf566eehqmmk78eyhk";
+        let heir2_key = BeneficiaryKey::Secp256k1(hex(OWNER_PUBKEY));
+
+        let params = EOLParams {
+            owner_key: Some(BeneficiaryKey::Secp256k1(Binary::from(vec![2; 33]))),
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            ..EOLParams::default()
+        };
+        let mut deps = mock_dependencies_with_params(&params);
+        let mut env = mock_env();
+        let key = (&Addr::unchecked("account"), "2");
+        let mut eol = EOL::new(Duration::from_seconds(100).unwrap(), env.block.time)
+            .with_beneficiary_commitments(vec![
+                beneficiary_commitment(heir1, b"salt1"),
+                beneficiary_commitment(heir2, b"salt2"),
+            ]);
+        eol.reveal_beneficiary(
+            Addr::unchecked(heir1),
+            BeneficiaryKey::Ed25519(hex(PUBKEY)),
+            b"salt1",
+        )
+        .unwrap();
+        EOLS.save(deps.as_mut().storage, key, &eol).unwrap();
+
+        let request = |signature: &str, sign_doc: &str, msgs: Vec<Any>| {
+            let mut request = auth_request("account", hex(signature));
+            request.authenticator_params = Some(to_json_binary(&params).unwrap());
+            request.sign_mode_tx_data.sign_mode_direct = hex(sign_doc);
+            request.tx_data.msgs = msgs;
+            request
+        };
+        let reveal = |pubkey: &BeneficiaryKey, salt: &[u8]| Any {
+            type_url: MsgExecuteContract::TYPE_URL.to_string(),
+            value: MsgExecuteContract {
+                sender: "account".to_string(),
+                contract: mock_env().contract.address.to_string(),
+                msg: to_json_vec(&ExecuteMsg::RevealBeneficiary {
+                    account: "account".to_string(),
+                    authenticator_id: "2".to_string(),
+                    address: heir2.to_string(),
+                    pubkey: pubkey.clone(),
+                    salt: Binary::from(salt),
+                })
+                .unwrap(),
+                funds: vec![],
+            }
+            .into(),
+        };
+
+        // revealed beneficiary, account is still alive
+        assert_eq!(
+            authenticate(
+                deps.as_mut(),
+                env.clone(),
+                request(SIGNATURE, SIGN_DOC, vec![])
+            )
+            .unwrap_err(),
+            EOLError::TimeInBoundsError {
+                out_of_bounds_limit: env.block.time.plus_seconds(100)
+            }
+            .into()
+        );
+
+        env.block.time = env.block.time.plus_seconds(101);
+
+        // revealed beneficiary
+        authenticate(
+            deps.as_mut(),
+            env.clone(),
+            request(SIGNATURE, SIGN_DOC, vec![]),
+        )
+        .unwrap();
+        let session = SESSIONS
+            .load(deps.as_ref().storage, (&Addr::unchecked("account"), "2", 0))
+            .unwrap();
+        assert_eq!(
+            session.signer,
+            Signer::CommittedBeneficiary {
+                address: Addr::unchecked(heir1)
+            }
+        );
+
+        // committed beneficiary without reveal
+        assert_eq!(
+            authenticate(
+                deps.as_mut(),
+                env.clone(),
+                request(OWNER_SIGNATURE, OWNER_SIGN_DOC, vec![])
+            )
+            .unwrap_err(),
+            ContractError::Unauthorized {}
+        );
+
+        // committed beneficiary revealing with a wrong salt
+        assert_eq!(
+            authenticate(
+                deps.as_mut(),
+                env.clone(),
+                request(
+                    OWNER_SIGNATURE,
+                    OWNER_SIGN_DOC,
+                    vec![reveal(&heir2_key, b"salt1")]
+                )
+            )
+            .unwrap_err(),
+            ContractError::Unauthorized {}
+        );
+
+        // committed beneficiary revealing with a key of another address
+        assert_eq!(
+            authenticate(
+                deps.as_mut(),
+                env.clone(),
+                request(
+                    OWNER_SIGNATURE,
+                    OWNER_SIGN_DOC,
+                    vec![reveal(&BeneficiaryKey::Ed25519(hex(PUBKEY)), b"salt2")]
+                )
+            )
+            .unwrap_err(),
+            ContractError::Unauthorized {}
+        );
+
+        // knowing the address, salt and key doesn't make up for the signature
+        assert_eq!(
+            authenticate(
+                deps.as_mut(),
+                env.clone(),
+                request("", OWNER_SIGN_DOC, vec![reveal(&heir2_key, b"salt2")])
+            )
+            .unwrap_err(),
+            ContractError::Unauthorized {}
+        );
+
+        // committed beneficiary revealing inline
+        authenticate(
+            deps.as_mut(),
+            env,
+            request(
+                OWNER_SIGNATURE,
+                OWNER_SIGN_DOC,
+                vec![reveal(&heir2_key, b"salt2")],
+            ),
+        )
+        .unwrap();
+        let session = SESSIONS
+            .load(deps.as_ref().storage, (&Addr::unchecked("account"), "2", 0))
+            .unwrap();
+        assert_eq!(
+            session.signer,
+            Signer::CommittedBeneficiary {
+                address: Addr::unchecked(heir2)
+            }
+        );
+    }
+
+    #[test]
+    fn test_authenticate_takeover() {
+        // secp256k1 test vector taken from cosmwasm-crypto
+        let sign_doc = hex("5c868fedb8026979ebd26f1ba07c27eedf4ff6d10443505a96ecaf21ba8c4f0937b3cd23ffdc3dd429d4cd1905fb8dbcceeff1350020e18b58d2ba70887baa3a9b783ad30d3fbf210331cdd7df8d77defa398cdacdfc2e359c7ba4cae46bb74401deb417f8b912a1aa966aeeba9c39c7dd22479ae2b30719dca2f2206c5eb4b7");
+        let signature = hex("207082eb2c3dfa0b454e0906051270ba4074ac93760ba9e7110cd9471475111151eb0dbbc9920e72146fb564f99d039802bf6ef2561446eb126ef364d21ee9c4");
+        let pubkey = hex("03051c1ee2190ecfb174bfe4f90763f2b4ff7517b70a2aec1876ebcfd644c4633f");
+
+        let params = |allow_takeover: bool| {
+            to_json_binary(&EOLParams {
+                inactivity_period: Duration::from_seconds(100).unwrap(),
+                beneficiaries: vec![BeneficiaryKey::Secp256k1(pubkey.clone())],
+                allow_takeover,
+                ..EOLParams::default()
+            })
+            .unwrap()
+        };
+        let authenticators = || {
+            get_authenticators_query_handler(Box::new(|req| {
+                let authenticator = |id: u64| AccountAuthenticator {
+                    id,
+                    r#type: "SignatureVerification".to_string(),
+                    config: vec![],
+                };
+                match req.account.as_str() {
+                    "account" => ContractResult::Ok(GetAuthenticatorsResponse {
+                        account_authenticators: vec![authenticator(1), authenticator(2)],
+                    }),
+                    _ => ContractResult::Err("not found".to_string()),
+                }
+            }))
+        };
+        let mut deps = mock_dependencies_with_stargate_querier(
+            &[],
+            chain_query_handlers(
+                registered_params_query_handler(MOCK_CONTRACT_ADDR, &params(false)),
+                authenticators(),
+            ),
+        );
+        let mut env = mock_env();
+        EOLS.save(
+            deps.as_mut().storage,
+            (&Addr::unchecked("account"), "2"),
+            &EOL::new(Duration::from_seconds(100).unwrap(), env.block.time),
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(101);
+
+        let msgs: Vec<Any> = vec![
+            Any {
+                type_url: MsgAddAuthenticator::TYPE_URL.to_string(),
+                value: MsgAddAuthenticator {
+                    sender: "account".to_string(),
+                    r#type: "SignatureVerification".to_string(),
+                    data: pubkey.to_vec(),
+                }
+                .into(),
+            },
+            Any {
+                type_url: MsgRemoveAuthenticator::TYPE_URL.to_string(),
+                value: MsgRemoveAuthenticator {
+                    sender: "account".to_string(),
+                    id: 1,
+                }
+                .into(),
+            },
+        ];
+        let request = |allow_takeover: bool, msg_index: usize| {
+            let mut request = auth_request("beneficiary", signature.clone());
+            request.authenticator_params = Some(params(allow_takeover));
+            request.sign_mode_tx_data.sign_mode_direct = sign_doc.clone();
+            request.tx_data.msgs = msgs.clone();
+            request.msg = msgs[msg_index].clone();
+            request.msg_index = msg_index as u64;
+            request
+        };
+
+        // takeover is not enabled
+        assert_eq!(
+            authenticate(deps.as_mut(), env.clone(), request(false, 0)).unwrap_err(),
+            EOLError::message_not_allowed(MsgAddAuthenticator::TYPE_URL).into()
+        );
+
+        deps.querier.update_stargate_handler(chain_query_handlers(
+            registered_params_query_handler(MOCK_CONTRACT_ADDR, &params(true)),
+            authenticators(),
+        ));
+        for msg_index in 0..msgs.len() {
+            authenticate(deps.as_mut(), env.clone(), request(true, msg_index)).unwrap();
+            let session = SESSIONS
+                .load(
+                    deps.as_ref().storage,
+                    (&Addr::unchecked("account"), "2", msg_index as u64),
+                )
+                .unwrap();
+            assert!(session.takeover);
+        }
+
+        // nested in a composite authenticator, its siblings would survive the takeover
+        let composite = to_json_vec(&[
+            SubAuthenticatorData {
+                r#type: SIGNATURE_VERIFICATION.to_string(),
+                config: pubkey.to_vec(),
+            },
+            SubAuthenticatorData {
+                r#type: COSMWASM_AUTHENTICATOR.to_string(),
+                config: to_json_vec(&CosmwasmAuthenticatorData {
+                    contract: MOCK_CONTRACT_ADDR.to_string(),
+                    params: params(true).to_vec(),
+                })
+                .unwrap(),
+            },
+        ])
+        .unwrap();
+        deps.querier.update_stargate_handler(chain_query_handlers(
+            get_authenticator_query_handler(Box::new(move |req| {
+                ContractResult::Ok(GetAuthenticatorResponse {
+                    account_authenticator: Some(AccountAuthenticator {
+                        id: req.authenticator_id,
+                        r#type: "AllOf".to_string(),
+                        config: composite.clone(),
+                    }),
+                })
+            })),
+            authenticators(),
+        ));
+        EOLS.save(
+            deps.as_mut().storage,
+            (&Addr::unchecked("account"), "2.1"),
+            &EOL::new(Duration::from_seconds(100).unwrap(), mock_env().block.time),
+        )
+        .unwrap();
+        let mut nested = request(true, 0);
+        nested.authenticator_id = "2.1".to_string();
+        assert_eq!(
+            authenticate(deps.as_mut(), env.clone(), nested).unwrap_err(),
+            EOLError::invalid_takeover(
+                "authenticator must not be nested in a composite authenticator"
+            )
+            .into()
+        );
+
+        // the owner's authenticator must be removed as well
+        deps.querier.update_stargate_handler(chain_query_handlers(
+            registered_params_query_handler(MOCK_CONTRACT_ADDR, &params(true)),
+            authenticators(),
+        ));
+        let mut incomplete = request(true, 0);
+        incomplete.tx_data.msgs.pop();
+        assert_eq!(
+            authenticate(deps.as_mut(), env, incomplete).unwrap_err(),
+            EOLError::invalid_takeover(
+                "must remove every other authenticator of the account exactly once"
+            )
+            .into()
+        );
+    }
+
+    #[rstest]
+    #[case::account_pays(FeePolicy::Account, "account", None, &["beneficiary"], Ok(()))]
+    #[case::granter_required(
+        FeePolicy::Granter,
+        "account",
+        None,
+        &["beneficiary"],
+        Err(EOLError::invalid_fee_payment("fees must be covered by a fee granter"))
+    )]
+    #[case::account_as_granter(
+        FeePolicy::Granter,
+        "beneficiary",
+        Some("account"),
+        &["beneficiary"],
+        Err(EOLError::invalid_fee_payment("fees must be covered by a fee granter"))
+    )]
+    #[case::granter(FeePolicy::Granter, "account", Some("granter"), &["beneficiary"], Ok(()))]
+    #[case::beneficiary_required(
+        FeePolicy::Beneficiary,
+        "account",
+        None,
+        &["beneficiary"],
+        Err(EOLError::invalid_fee_payment("fees must be paid by the beneficiary"))
+    )]
+    #[case::beneficiary_not_signing(
+        FeePolicy::Beneficiary,
+        "someone",
+        None,
+        &["beneficiary"],
+        Err(EOLError::invalid_fee_payment("fees must be paid by the beneficiary"))
+    )]
+    #[case::beneficiary(FeePolicy::Beneficiary, "beneficiary", None, &["beneficiary"], Ok(()))]
+    fn test_authenticate_fee_policy(
+        #[case] fee_policy: FeePolicy,
+        #[case] fee_payer: &str,
+        #[case] fee_granter: Option<&str>,
+        #[case] signers: &[&str],
+        #[case] expected: Result<(), EOLError>,
+    ) {
+        let params = EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            beneficiaries: vec![BeneficiaryKey::Ed25519(hex(PUBKEY))],
+            fee_policy,
+            ..EOLParams::default()
+        };
+        let mut deps = mock_dependencies_with_params(&params);
+        let mut env = mock_env();
+        EOLS.save(
+            deps.as_mut().storage,
+            (&Addr::unchecked("account"), "2"),
+            &EOL::new(Duration::from_seconds(100).unwrap(), env.block.time),
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(101);
+
+        let mut request = auth_request("beneficiary", hex(SIGNATURE));
+        request.authenticator_params = Some(to_json_binary(&params).unwrap());
+        request.fee_payer = Addr::unchecked(fee_payer);
+        request.fee_granter = fee_granter.map(Addr::unchecked);
+        request.signature_data.signers = signers.iter().map(|s| Addr::unchecked(*s)).collect();
+
+        assert_eq!(
+            authenticate(deps.as_mut(), env, request).map(|_| ()),
+            expected.map_err(ContractError::from)
+        );
+    }
+
+    #[test]
+    fn test_authenticate_fee_budget() {
+        let params = EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            beneficiaries: vec![BeneficiaryKey::Ed25519(hex(PUBKEY))],
+            fee_budget: Some(FeeBudget {
+                limit: coins(1_000, "uosmo"),
+                period: Duration::from_seconds(100).unwrap(),
+            }),
+            ..EOLParams::default()
+        };
+        let mut deps = mock_dependencies_with_params(&params);
+        let mut env = mock_env();
+        let key = (&Addr::unchecked("account"), "2");
+        let mut eol = EOL::new(Duration::from_seconds(100).unwrap(), env.block.time);
+        env.block.time = env.block.time.plus_seconds(101);
+        eol.fee_spending = FeeSpending {
+            period_start: env.block.time,
+            spent: coins(900, "uosmo"),
+        };
+        EOLS.save(deps.as_mut().storage, key, &eol).unwrap();
+
+        let request = |fee: u128, fee_granter: Option<&str>| {
+            let mut request = auth_request("beneficiary", hex(SIGNATURE));
+            request.authenticator_params = Some(to_json_binary(&params).unwrap());
+            request.fee = coins(fee, "uosmo");
+            request.fee_granter = fee_granter.map(Addr::unchecked);
+            request
+        };
+
+        authenticate(deps.as_mut(), env.clone(), request(100, None)).unwrap();
+        assert_eq!(
+            authenticate(deps.as_mut(), env.clone(), request(101, None)).unwrap_err(),
+            EOLError::FeeBudgetExceeded {
+                denom: "uosmo".to_string(),
+                limit: 1_000u128.into(),
+            }
+            .into()
+        );
+
+        // fees covered by someone else don't count against the budget
+        authenticate(deps.as_mut(), env, request(101, Some("granter"))).unwrap();
+    }
+}
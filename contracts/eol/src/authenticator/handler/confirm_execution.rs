@@ -1,164 +1,734 @@
-use cosmwasm_std::{DepsMut, Env, Response};
+use std::str::FromStr;
+
+use cosmwasm_std::{DepsMut, Env, Order, Response, StdResult};
 use cw_authenticator::ConfirmExecutionRequest;
+use cw_storage_plus::Bound;
+use osmosis_std::types::osmosis::smartaccount::v1beta1::SmartaccountQuerier;
 
+use crate::authenticator::{AuthenticatorError, CompositeId};
+use crate::eol::asset::AssetMsg;
+use crate::eol::error::EOLError;
+use crate::eol::reserve::ensure_reserve_kept;
+use crate::eol::staking::is_staked;
+use crate::eol::takeover::{ensure_taken_over, ensure_takeover_msg};
+use crate::eol::unpack::unpack;
+use crate::eol::withdrawal::outflows;
+use crate::eol::Signer;
+use crate::price::PriceError;
+use crate::state::{ASSET_TRANSFERS, EOLS, PRICE_RESOLUTION_CONFIG, SESSIONS};
 use crate::ContractError;
 
-use super::validate_and_parse_params;
+use super::verify_and_parse_params;
 
 pub fn confirm_execution(
-    mut deps: DepsMut,
+    deps: DepsMut,
     env: Env,
     ConfirmExecutionRequest {
         authenticator_id,
         account,
+        msg,
+        msg_index,
         authenticator_params,
         ..
     }: ConfirmExecutionRequest,
 ) -> Result<Response, ContractError> {
-    let _ = validate_and_parse_params(authenticator_params)?;
+    let params = verify_and_parse_params(
+        &deps.querier,
+        &env.contract.address,
+        &account,
+        &authenticator_id,
+        authenticator_params,
+    )?;
+
+    // the message went through, its session is no longer needed
+    let session_key = (&account, authenticator_id.as_str(), msg_index);
+    let session = SESSIONS
+        .may_load(deps.storage, session_key)?
+        .filter(|session| session.is_current(&env.block));
+    SESSIONS.remove(deps.storage, session_key);
+
+    let Some(session) = session else {
+        return Ok(Response::new().add_attribute("action", "confirm_execution"));
+    };
+
+    if session.is_beneficiary() {
+        ensure_reserve_kept(&deps.querier, &account, &session.reserved)?;
+
+        // bank balances miss cw20 and cw721 tokens, their transfers are tallied per beneficiary
+        let asset_msgs = unpack(&msg)?
+            .iter()
+            .map(AssetMsg::decode)
+            .filter_map(Result::transpose)
+            .collect::<Result<Vec<_>, _>>()?;
+        if asset_msgs
+            .iter()
+            .any(|asset_msg| matches!(asset_msg, AssetMsg::Transfer(_)))
+        {
+            let beneficiary = session.signer.id();
+            let key = (&account, authenticator_id.as_str(), beneficiary.as_str());
+            let mut transfers = ASSET_TRANSFERS
+                .may_load(deps.storage, key)?
+                .unwrap_or_default();
+            for asset_msg in &asset_msgs {
+                let rule = params.ensure_asset_msg_allowed(asset_msg)?;
+                if let AssetMsg::Transfer(transfer) = asset_msg {
+                    transfers.record(rule, transfer)?;
+                }
+            }
+            if !session.simulate {
+                ASSET_TRANSFERS.save(deps.storage, key, &transfers)?;
+            }
+        }
+
+        // comparing balances catches tokens moved indirectly, e.g. by swaps or contract calls
+        if params.tracks_balances() {
+            let balances = deps.querier.query_all_balances(&account)?;
+            params.ensure_denoms_kept(&session.balances, &balances)?;
+
+            if params.withdrawal_cap.is_some() || params.staking.is_some() {
+                let outflows = outflows(&session.balances, &balances);
+                let key = (&account, authenticator_id.as_str());
+                let mut eol = EOLS.load(deps.storage, key)?;
+
+                if let Some(cap) = &params.withdrawal_cap {
+                    let config = PRICE_RESOLUTION_CONFIG
+                        .may_load(deps.storage)?
+                        .ok_or(PriceError::NotConfigured {})?;
+                    let value = config.value(
+                        deps.storage,
+                        &deps.querier,
+                        &outflows,
+                        env.block.time,
+                        session.simulate,
+                    )?;
+                    eol.withdrawals.withdraw(cap, value, env.block.time)?;
+                }
+
+                // the cap adds up across messages and transactions until all stake is unbonded
+                if let Some(staking) = &params.staking {
+                    if is_staked(&deps.querier, &account)? {
+                        staking.record_outflows(&mut eol.staked_outflows, &outflows)?;
+                    }
+                }
+
+                if !session.simulate {
+                    EOLS.save(deps.storage, key, &eol)?;
+                }
+            }
+
+            // later messages of the transaction only account for what they move themselves
+            let later_sessions = SESSIONS
+                .prefix((&account, authenticator_id.as_str()))
+                .range(
+                    deps.storage,
+                    Some(Bound::exclusive(msg_index)),
+                    None,
+                    Order::Ascending,
+                )
+                .collect::<StdResult<Vec<_>>>()?;
+            for (index, mut later) in later_sessions {
+                if later.is_current(&env.block) {
+                    later.balances = balances.clone();
+                    SESSIONS.save(
+                        deps.storage,
+                        (&account, authenticator_id.as_str(), index),
+                        &later,
+                    )?;
+                }
+            }
+        }
+    }
+
+    if !session.takeover {
+        return Ok(Response::new().add_attribute("action", "confirm_execution"));
+    }
+
+    // re-validate the takeover against what was actually executed
+    let pubkey = match session.signer {
+        Signer::Beneficiary { index } => params.takeover_key(index),
+        _ => None,
+    }
+    .ok_or_else(|| EOLError::invalid_takeover("signer can't take the account over"))?;
+    ensure_takeover_msg(msg_index as usize, &msg, &account, pubkey)?;
+
+    let authenticators = SmartaccountQuerier::new(&deps.querier)
+        .get_authenticators(account.to_string())?
+        .account_authenticators;
+    let root = CompositeId::from_str(&authenticator_id)
+        .map_err(AuthenticatorError::from)?
+        .root;
+    ensure_taken_over(&authenticators, pubkey, root)?;
+
+    // a simulated takeover is validated all the same but never claims the account
+    if session.simulate {
+        return Ok(Response::new().add_attribute("action", "confirm_execution"));
+    }
+
+    let key = (&account, authenticator_id.as_str());
+    let mut eol = EOLS.load(deps.storage, key)?;
+    eol.claim(env.block.time);
+    EOLS.save(deps.storage, key, &eol)?;
 
     Ok(Response::new()
-        .add_attribute("action", "confirm_execution"))
+        .add_attribute("action", "confirm_execution")
+        .add_attribute("status", "claimed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eol::BeneficiaryKey;
+    use crate::eol::{takeover::SIGNATURE_VERIFICATION, EOLParams, EOLStatus, Session, EOL};
+    use crate::eol::{AssetRule, Duration, StakingPreset, WithdrawalCap};
+    use crate::price::{PriceInfo, PriceResolutionConfig};
+    use crate::state::ASSET_TRANSFERS;
+    use crate::state::{PRICE_INFOS, PRICE_RESOLUTION_CONFIG};
+    use crate::test_helper::mock_stargate_querier::{
+        arithmetic_twap_to_now_query_handler, chain_query_handlers,
+        delegator_staking_query_handler, get_authenticators_query_handler,
+        mock_dependencies_with_registered_params, mock_dependencies_with_stargate_querier,
+        registered_params_query_handler,
+    };
+    use cosmwasm_std::{
+        coin, coins,
+        testing::{mock_env, MOCK_CONTRACT_ADDR},
+        to_json_binary, Addr, Binary, ContractResult, Decimal, Timestamp, Uint128,
+    };
+    use cw_authenticator::Any;
+    use osmosis_std::types::cosmwasm::wasm::v1::MsgExecuteContract;
+    use osmosis_std::types::osmosis::smartaccount::v1beta1::{
+        AccountAuthenticator, GetAuthenticatorsResponse, MsgAddAuthenticator,
+    };
+    use osmosis_std::types::osmosis::twap::v1beta1::ArithmeticTwapToNowResponse;
+
+    const PUBKEY: &[u8] = &[2; 33];
+
+    #[test]
+    fn test_confirm_execution_claims_on_takeover() {
+        let params = to_json_binary(&EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            beneficiaries: vec![BeneficiaryKey::Secp256k1(Binary::from(PUBKEY))],
+            allow_takeover: true,
+            ..EOLParams::default()
+        })
+        .unwrap();
+        let mut deps = mock_dependencies_with_stargate_querier(
+            &[],
+            chain_query_handlers(
+                registered_params_query_handler(MOCK_CONTRACT_ADDR, &params),
+                get_authenticators_query_handler(Box::new(|_| {
+                    ContractResult::Ok(GetAuthenticatorsResponse {
+                        account_authenticators: vec![
+                            AccountAuthenticator {
+                                id: 2,
+                                r#type: "CosmwasmAuthenticatorV1".to_string(),
+                                config: vec![],
+                            },
+                            AccountAuthenticator {
+                                id: 3,
+                                r#type: SIGNATURE_VERIFICATION.to_string(),
+                                config: PUBKEY.to_vec(),
+                            },
+                        ],
+                    })
+                })),
+            ),
+        );
+        let env = mock_env();
+        let account = Addr::unchecked("account");
+        EOLS.save(
+            deps.as_mut().storage,
+            (&account, "2"),
+            &EOL::new(
+                Duration::from_seconds(100).unwrap(),
+                Timestamp::from_seconds(0),
+            ),
+        )
+        .unwrap();
+
+        let request = || ConfirmExecutionRequest {
+            authenticator_id: "2".to_string(),
+            account: account.clone(),
+            fee_payer: account.clone(),
+            fee_granter: None,
+            fee: vec![],
+            msg: Any {
+                type_url: MsgAddAuthenticator::TYPE_URL.to_string(),
+                value: MsgAddAuthenticator {
+                    sender: "account".to_string(),
+                    r#type: SIGNATURE_VERIFICATION.to_string(),
+                    data: PUBKEY.to_vec(),
+                }
+                .into(),
+            },
+            msg_index: 0,
+            authenticator_params: Some(params.clone()),
+        };
+
+        // without a takeover session nothing is claimed
+        confirm_execution(deps.as_mut(), env.clone(), request()).unwrap();
+        assert_eq!(
+            EOLS.load(deps.as_ref().storage, (&account, "2"))
+                .unwrap()
+                .status,
+            EOLStatus::Active
+        );
+
+        // a simulated takeover doesn't claim the account
+        SESSIONS
+            .save(
+                deps.as_mut().storage,
+                (&account, "2", 0),
+                &Session::new(Signer::Beneficiary { index: 0 }, &env.block)
+                    .with_takeover(true)
+                    .with_simulate(true),
+            )
+            .unwrap();
+        confirm_execution(deps.as_mut(), env.clone(), request()).unwrap();
+        assert_eq!(
+            EOLS.load(deps.as_ref().storage, (&account, "2"))
+                .unwrap()
+                .status,
+            EOLStatus::Active
+        );
+
+        SESSIONS
+            .save(
+                deps.as_mut().storage,
+                (&account, "2", 0),
+                &Session::new(Signer::Beneficiary { index: 0 }, &env.block).with_takeover(true),
+            )
+            .unwrap();
+        confirm_execution(deps.as_mut(), env.clone(), request()).unwrap();
+
+        assert_eq!(
+            EOLS.load(deps.as_ref().storage, (&account, "2"))
+                .unwrap()
+                .status,
+            EOLStatus::Claimed { at: env.block.time }
+        );
+        assert!(!SESSIONS.has(deps.as_ref().storage, (&account, "2", 0)));
+    }
+
+    #[test]
+    fn test_confirm_execution_keeps_reserve() {
+        let params = to_json_binary(&EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            ..EOLParams::default()
+        })
+        .unwrap();
+        let mut deps = mock_dependencies_with_registered_params(&coins(99, "uosmo"), &params);
+        let env = mock_env();
+        let account = env.contract.address.clone();
+        let request = || ConfirmExecutionRequest {
+            authenticator_id: "2".to_string(),
+            account: account.clone(),
+            fee_payer: account.clone(),
+            fee_granter: None,
+            fee: vec![],
+            msg: Any {
+                type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+                value: Binary::default(),
+            },
+            msg_index: 0,
+            authenticator_params: Some(params.clone()),
+        };
+        let save_session = |deps: &mut DepsMut, signer: Signer| {
+            let mut session = Session::new(signer, &env.block);
+            session.reserved = coins(100, "uosmo");
+            SESSIONS
+                .save(deps.storage, (&account, "2", 0), &session)
+                .unwrap();
+        };
+
+        save_session(&mut deps.as_mut(), Signer::Beneficiary { index: 0 });
+        assert_eq!(
+            confirm_execution(deps.as_mut(), env.clone(), request()).unwrap_err(),
+            EOLError::ReserveBreached {
+                denom: "uosmo".to_string(),
+                reserve: 100u128.into(),
+                balance: 99u128.into(),
+            }
+            .into()
+        );
+
+        // the owner is free to spend the reserve
+        save_session(&mut deps.as_mut(), Signer::Owner);
+        confirm_execution(deps.as_mut(), env.clone(), request()).unwrap();
+
+        deps.querier
+            .update_balance(account.clone(), coins(100, "uosmo"));
+        save_session(&mut deps.as_mut(), Signer::Beneficiary { index: 0 });
+        confirm_execution(deps.as_mut(), env, request()).unwrap();
+    }
+
+    #[test]
+    fn test_confirm_execution_keeps_restricted_denoms() {
+        let params = to_json_binary(&EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            allowed_denoms: Some(vec!["uosmo".to_string()]),
+            ..EOLParams::default()
+        })
+        .unwrap();
+        let mut deps = mock_dependencies_with_registered_params(&[coin(100, "uosmo")], &params);
+        let env = mock_env();
+        let account = env.contract.address.clone();
+        let mut session = Session::new(Signer::Beneficiary { index: 0 }, &env.block);
+        session.balances = vec![coin(100, "ugov"), coin(100, "uosmo")];
+        SESSIONS
+            .save(deps.as_mut().storage, (&account, "2", 0), &session)
+            .unwrap();
+
+        // ugov got swapped away
+        let err = confirm_execution(
+            deps.as_mut(),
+            env,
+            ConfirmExecutionRequest {
+                authenticator_id: "2".to_string(),
+                account: account.clone(),
+                fee_payer: account.clone(),
+                fee_granter: None,
+                fee: vec![],
+                msg: Any {
+                    type_url: "/osmosis.poolmanager.v1beta1.MsgSwapExactAmountIn".to_string(),
+                    value: Binary::default(),
+                },
+                msg_index: 0,
+                authenticator_params: Some(params),
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(err, EOLError::denom_not_allowed("ugov").into());
+    }
+
+    #[test]
+    fn test_confirm_execution_caps_withdrawals() {
+        let params = to_json_binary(&EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            withdrawal_cap: Some(WithdrawalCap {
+                limit: Uint128::new(500),
+                period: Duration::from_seconds(604_800).unwrap(),
+            }),
+            ..EOLParams::default()
+        })
+        .unwrap();
+        let mut deps = mock_dependencies_with_stargate_querier(
+            &[],
+            chain_query_handlers(
+                registered_params_query_handler(MOCK_CONTRACT_ADDR, &params),
+                arithmetic_twap_to_now_query_handler(Box::new(|_| {
+                    ContractResult::Ok(ArithmeticTwapToNowResponse {
+                        arithmetic_twap: "2".to_string(),
+                    })
+                })),
+            ),
+        );
+        let env = mock_env();
+        let account = env.contract.address.clone();
+        PRICE_RESOLUTION_CONFIG
+            .save(
+                deps.as_mut().storage,
+                &PriceResolutionConfig {
+                    quote_denom: "uusdc".to_string(),
+                    staleness_threshold: 3_600_000_000_000u64.into(),
+                    twap_duration: 3_600_000_000_000u64.into(),
+                },
+            )
+            .unwrap();
+        PRICE_INFOS
+            .save(
+                deps.as_mut().storage,
+                "uosmo",
+                &PriceInfo {
+                    price: Decimal::percent(150),
+                    last_updated_time: env.block.time,
+                    swap_routes: vec![],
+                },
+            )
+            .unwrap();
+        EOLS.save(
+            deps.as_mut().storage,
+            (&account, "2"),
+            &EOL::new(
+                Duration::from_seconds(100).unwrap(),
+                Timestamp::from_seconds(0),
+            ),
+        )
+        .unwrap();
+
+        // both messages start from the balances snapshotted before the transaction
+        for msg_index in 0..2 {
+            let mut session = Session::new(Signer::Beneficiary { index: 0 }, &env.block);
+            session.balances = vec![coin(1_000, "uosmo"), coin(1_000, "uusdc")];
+            SESSIONS
+                .save(deps.as_mut().storage, (&account, "2", msg_index), &session)
+                .unwrap();
+        }
+
+        let request = |msg_index: u64| ConfirmExecutionRequest {
+            authenticator_id: "2".to_string(),
+            account: account.clone(),
+            fee_payer: account.clone(),
+            fee_granter: None,
+            fee: vec![],
+            msg: Any {
+                type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+                value: Binary::default(),
+            },
+            msg_index,
+            authenticator_params: Some(params.clone()),
+        };
+
+        // 200uosmo at 1.5 plus 100uusdc
+        deps.querier.update_balance(
+            account.clone(),
+            vec![coin(800, "uosmo"), coin(900, "uusdc")],
+        );
+        confirm_execution(deps.as_mut(), env.clone(), request(0)).unwrap();
+        assert_eq!(
+            EOLS.load(deps.as_ref().storage, (&account, "2"))
+                .unwrap()
+                .withdrawals
+                .value,
+            Uint128::new(400)
+        );
+
+        // the second message alone moves 101uusdc more
+        deps.querier.update_balance(
+            account.clone(),
+            vec![coin(800, "uosmo"), coin(799, "uusdc")],
+        );
+        assert_eq!(
+            confirm_execution(deps.as_mut(), env, request(1)).unwrap_err(),
+            EOLError::WithdrawalCapExceeded {
+                limit: Uint128::new(500)
+            }
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_confirm_execution_caps_untracked_withdrawals() {
+        let params = to_json_binary(&EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            withdrawal_cap: Some(WithdrawalCap {
+                limit: Uint128::new(500),
+                period: Duration::from_seconds(604_800).unwrap(),
+            }),
+            ..EOLParams::default()
+        })
+        .unwrap();
+        let mut deps = mock_dependencies_with_stargate_querier(
+            &[],
+            chain_query_handlers(
+                registered_params_query_handler(MOCK_CONTRACT_ADDR, &params),
+                arithmetic_twap_to_now_query_handler(Box::new(|_| {
+                    ContractResult::Err("no twap".to_string())
+                })),
+            ),
+        );
+        let env = mock_env();
+        let account = env.contract.address.clone();
+        PRICE_RESOLUTION_CONFIG
+            .save(
+                deps.as_mut().storage,
+                &PriceResolutionConfig {
+                    quote_denom: "uusdc".to_string(),
+                    staleness_threshold: 3_600_000_000_000u64.into(),
+                    twap_duration: 3_600_000_000_000u64.into(),
+                },
+            )
+            .unwrap();
+        EOLS.save(
+            deps.as_mut().storage,
+            (&account, "2"),
+            &EOL::new(
+                Duration::from_seconds(100).unwrap(),
+                Timestamp::from_seconds(0),
+            ),
+        )
+        .unwrap();
+        let mut session = Session::new(Signer::Beneficiary { index: 0 }, &env.block);
+        session.balances = vec![coin(1_000_000, "uatom")];
+        SESSIONS
+            .save(deps.as_mut().storage, (&account, "2", 0), &session)
+            .unwrap();
+
+        // the whole uatom balance leaves, way over the cap were it priced
+        deps.querier.update_balance(account.clone(), vec![]);
+        let request = ConfirmExecutionRequest {
+            authenticator_id: "2".to_string(),
+            account: account.clone(),
+            fee_payer: account.clone(),
+            fee_granter: None,
+            fee: vec![],
+            msg: Any {
+                type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+                value: Binary::default(),
+            },
+            msg_index: 0,
+            authenticator_params: Some(params),
+        };
+
+        assert_eq!(
+            confirm_execution(deps.as_mut(), env, request).unwrap_err(),
+            PriceError::UntrackedDenom {
+                denom: "uatom".to_string()
+            }
+            .into()
+        );
+        assert!(EOLS
+            .load(deps.as_ref().storage, (&account, "2"))
+            .unwrap()
+            .withdrawals
+            .value
+            .is_zero());
+    }
+
+    #[test]
+    fn test_confirm_execution_caps_staked_outflows() {
+        let env = mock_env();
+        let account = env.contract.address.clone();
+        let confirm = |delegated: bool, before: u128, after: u128| {
+            let params = to_json_binary(&EOLParams {
+                inactivity_period: Duration::from_seconds(100).unwrap(),
+                staking: Some(StakingPreset {
+                    transfer_cap: coins(1_000, "uosmo"),
+                }),
+                ..EOLParams::default()
+            })
+            .unwrap();
+            let mut deps = mock_dependencies_with_stargate_querier(
+                &[],
+                chain_query_handlers(
+                    registered_params_query_handler(MOCK_CONTRACT_ADDR, &params),
+                    delegator_staking_query_handler(delegated, false),
+                ),
+            );
+            deps.querier
+                .update_balance(account.clone(), coins(after, "uosmo"));
+            let mut eol = EOL::new(
+                Duration::from_seconds(100).unwrap(),
+                Timestamp::from_seconds(0),
+            );
+            eol.staked_outflows = coins(400, "uosmo");
+            EOLS.save(deps.as_mut().storage, (&account, "2"), &eol)
+                .unwrap();
+            let mut session = Session::new(Signer::Beneficiary { index: 0 }, &env.block);
+            session.balances = coins(before, "uosmo");
+            SESSIONS
+                .save(deps.as_mut().storage, (&account, "2", 0), &session)
+                .unwrap();
+
+            let request = ConfirmExecutionRequest {
+                authenticator_id: "2".to_string(),
+                account: account.clone(),
+                fee_payer: account.clone(),
+                fee_granter: None,
+                fee: vec![],
+                msg: Any {
+                    type_url: "/osmosis.poolmanager.v1beta1.MsgSwapExactAmountIn".to_string(),
+                    value: Binary::default(),
+                },
+                msg_index: 0,
+                authenticator_params: Some(params),
+            };
+            confirm_execution(deps.as_mut(), env.clone(), request).map(|_| {
+                EOLS.load(&deps.storage, (&account, "2"))
+                    .unwrap()
+                    .staked_outflows
+            })
+        };
+
+        // adds up with what left in earlier transactions
+        assert_eq!(confirm(true, 5_000, 4_400).unwrap(), coins(1_000, "uosmo"));
+        assert_eq!(
+            confirm(true, 5_000, 4_399).unwrap_err(),
+            EOLError::TransferCapExceeded {
+                denom: "uosmo".to_string(),
+                cap: Uint128::new(1_000),
+            }
+            .into()
+        );
+
+        // no cap once all stake is unbonded
+        assert_eq!(confirm(false, 5_000, 0).unwrap(), coins(400, "uosmo"));
+    }
+
+    #[test]
+    fn test_confirm_execution_tallies_asset_transfers() {
+        let params = to_json_binary(&EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            cw20_rules: Some(vec![AssetRule {
+                contract: "token".to_string(),
+                cap: Some(Uint128::new(100)),
+            }]),
+            ..EOLParams::default()
+        })
+        .unwrap();
+        let mut deps = mock_dependencies_with_registered_params(&[], &params);
+        let env = mock_env();
+        let account = env.contract.address.clone();
+        let signer = Signer::CommittedBeneficiary {
+            address: Addr::unchecked("heir"),
+        };
+        let request = |amount: u128, simulate: bool, deps: &mut DepsMut| {
+            SESSIONS
+                .save(
+                    deps.storage,
+                    (&account, "2", 0),
+                    &Session::new(signer.clone(), &env.block).with_simulate(simulate),
+                )
+                .unwrap();
+
+            ConfirmExecutionRequest {
+                authenticator_id: "2".to_string(),
+                account: account.clone(),
+                fee_payer: account.clone(),
+                fee_granter: None,
+                fee: vec![],
+                msg: Any {
+                    type_url: MsgExecuteContract::TYPE_URL.to_string(),
+                    value: MsgExecuteContract {
+                        sender: account.to_string(),
+                        contract: "token".to_string(),
+                        msg: format!(
+                            r#"{{"transfer":{{"recipient":"heir","amount":"{amount}"}}}}"#
+                        )
+                        .into_bytes(),
+                        funds: vec![],
+                    }
+                    .into(),
+                },
+                msg_index: 0,
+                authenticator_params: Some(params.clone()),
+            }
+        };
+        let key = (&account, "2", "heir");
+
+        let req = request(60, false, &mut deps.as_mut());
+        confirm_execution(deps.as_mut(), env.clone(), req).unwrap();
+        let totals = ASSET_TRANSFERS.load(deps.as_ref().storage, key).unwrap();
+        assert_eq!(totals.totals[0].amount, Uint128::new(60));
+
+        // simulations are not tallied
+        let req = request(40, true, &mut deps.as_mut());
+        confirm_execution(deps.as_mut(), env.clone(), req).unwrap();
+        assert_eq!(
+            ASSET_TRANSFERS.load(deps.as_ref().storage, key).unwrap(),
+            totals
+        );
+
+        let req = request(41, false, &mut deps.as_mut());
+        let err = confirm_execution(deps.as_mut(), env, req).unwrap_err();
+        assert_eq!(
+            err,
+            EOLError::AssetCapExceeded {
+                contract: "token".to_string(),
+                cap: Uint128::new(100),
+            }
+            .into()
+        );
+    }
 }
-//
-// #[cfg(test)]
-// mod tests {
-//     use cosmwasm_std::{
-//         testing::{mock_dependencies_with_balances, mock_env},
-//         to_json_binary, Addr, Binary, Coin, Response, Uint128,
-//     };
-//     use cw_authenticator::ConfirmExecutionRequest;
-//     use rstest::rstest;
-//
-//     use crate::period::Period;
-//     use crate::{
-//         price::PriceResolutionConfig,
-//         spend_limit::{SpendLimitError, SpendLimitParams, Spending},
-//         state::UNTRACKED_SPENT_FEES,
-//     };
-//
-//     use super::*;
-//
-//     #[rstest]
-//     #[case::spend_at_limit(1000, 500, 500, vec![Coin::new(1_000_000_000, "uosmo")], Ok(Response::new()
-//         .add_attribute("action", "confirm_execution")
-//         .add_attribute("spent", spent.to_string())
-//         .add_attribute("limit", limit.to_string())
-//     ))]
-//     #[case::spend_over_limit(1000, 500, 501, vec![Coin::new(1_000_000_000, "uosmo")], Err(SpendLimitError::overspend(500, 501).into())
-//     )]
-//     fn test_confirm_execution_only_spends_quoted_denom(
-//         #[case] initial_balance: u128,
-//         #[case] limit: u128,
-//         #[case] spent: u128,
-//         #[case] untracked_spent_fee: Vec<Coin>,
-//         #[case] expected: Result<Response, ContractError>,
-//     ) {
-//         use crate::fee::UntrackedSpentFee;
-//
-//         let fixed_balance = Coin::new(500, "uosmo");
-//         // Setup the environment
-//         let mut deps = mock_dependencies_with_balances(&[(
-//             "account",
-//             &[
-//                 Coin::new(initial_balance - spent, "uusdc"),
-//                 fixed_balance.clone(),
-//             ],
-//         )]);
-//
-//         let key = (&Addr::unchecked("account"), "2");
-//
-//         UNTRACKED_SPENT_FEES
-//             .save(
-//                 &mut deps.storage,
-//                 key,
-//                 &UntrackedSpentFee {
-//                     fee: untracked_spent_fee.clone(),
-//                     updated_at: mock_env().block.time,
-//                 },
-//             )
-//             .unwrap();
-//
-//         PRE_EXEC_BALANCES
-//             .save(
-//                 deps.as_mut().storage,
-//                 key,
-//                 &vec![Coin::new(initial_balance, "uusdc"), fixed_balance],
-//             )
-//             .unwrap();
-//
-//         SPENDINGS
-//             .save(&mut deps.storage, key, &Spending::default())
-//             .unwrap();
-//
-//         PRICE_RESOLUTION_CONFIG
-//             .save(
-//                 deps.as_mut().storage,
-//                 &PriceResolutionConfig {
-//                     quote_denom: "uusdc".to_string(),
-//                     staleness_threshold: 3_600_000_000_000u64.into(), // 1h
-//                     twap_duration: 3_600_000_000_000u64.into(),       // 1h
-//                 },
-//             )
-//             .unwrap();
-//
-//         // Confirm the execution
-//         let confirm_execution_request = ConfirmExecutionRequest {
-//             authenticator_id: "2".to_string(),
-//             account: Addr::unchecked("account"),
-//             fee_payer: Addr::unchecked("account"),
-//             fee_granter: None,
-//             fee: vec![],
-//             authenticator_params: Some(
-//                 to_json_binary(&SpendLimitParams {
-//                     limit: Uint128::new(limit),
-//                     reset_period: Period::Day,
-//                     time_limit: None,
-//                 })
-//                     .unwrap(),
-//             ),
-//             msg: cw_authenticator::Any {
-//                 type_url: "".to_string(),
-//                 value: Binary::default(),
-//             },
-//             msg_index: 0,
-//         };
-//
-//         let res = confirm_execution(deps.as_mut(), mock_env(), confirm_execution_request);
-//         match expected {
-//             Ok(expected_res) => {
-//                 assert_eq!(res.unwrap(), expected_res);
-//
-//                 // Verify that the spending is updated correctly
-//                 let spending = SPENDINGS.load(deps.as_ref().storage, key).unwrap();
-//                 assert_eq!(
-//                     spending,
-//                     Spending {
-//                         value_spent_in_period: spent.into(),
-//                         last_spent_at: mock_env().block.time
-//                     }
-//                 );
-//
-//                 // verify that the untracked spent fee is cleaned up
-//                 let untracked_spent_fee = UNTRACKED_SPENT_FEES
-//                     .may_load(deps.as_ref().storage, key)
-//                     .unwrap();
-//                 assert_eq!(untracked_spent_fee, None);
-//             }
-//             Err(expected_err) => {
-//                 assert_eq!(res.unwrap_err(), expected_err);
-//
-//                 // verify that untracked spent fee is not cleaned up
-//                 assert_eq!(
-//                     UNTRACKED_SPENT_FEES
-//                         .may_load(deps.as_ref().storage, key)
-//                         .unwrap(),
-//                     Some(UntrackedSpentFee {
-//                         fee: untracked_spent_fee,
-//                         updated_at: mock_env().block.time,
-//                     })
-//                 );
-//             }
-//         }
-//     }
-// }
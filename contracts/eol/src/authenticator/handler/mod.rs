@@ -1,6 +1,12 @@
-use cosmwasm_std::{from_json, Binary};
+use std::str::FromStr;
 
-use super::AuthenticatorError;
+use cosmwasm_std::{ensure, from_json, Addr, Binary, QuerierWrapper};
+use osmosis_std::types::osmosis::smartaccount::v1beta1::SmartaccountQuerier;
+
+use super::{
+    AuthenticatorError, ChildAuthenticator, CompositeAuthenticator, CompositeId, CompositeLimits,
+    CosmwasmAuthenticatorData, COSMWASM_AUTHENTICATOR,
+};
 use crate::eol::params::EOLParams;
 
 pub mod authenticate;
@@ -22,3 +28,50 @@ fn validate_and_parse_params(
     from_json(authenticator_params.as_slice())
         .map_err(AuthenticatorError::invalid_authenticator_params)
 }
+
+/// Validate and parse the authenticator_params, rejecting any that differ from the ones
+/// `contract` is configured with in the account's authenticator at `authenticator_id`
+fn verify_and_parse_params(
+    querier: &QuerierWrapper,
+    contract: &Addr,
+    account: &Addr,
+    authenticator_id: &str,
+    authenticator_params: Option<Binary>,
+) -> Result<EOLParams, AuthenticatorError> {
+    let authenticator_params =
+        authenticator_params.ok_or(AuthenticatorError::MissingAuthenticatorParams)?;
+
+    ensure!(
+        registered_params(querier, contract, account, authenticator_id)?
+            .is_some_and(|registered| registered == authenticator_params.as_slice()),
+        AuthenticatorError::params_mismatch(account.clone(), authenticator_id)
+    );
+
+    validate_and_parse_params(Some(authenticator_params))
+}
+
+/// Params `contract` is configured with in the account's authenticator at `authenticator_id`,
+/// `None` when no such authenticator is registered
+fn registered_params(
+    querier: &QuerierWrapper,
+    contract: &Addr,
+    account: &Addr,
+    authenticator_id: &str,
+) -> Result<Option<Vec<u8>>, AuthenticatorError> {
+    let CompositeId { root, path } = CompositeId::from_str(authenticator_id)?;
+
+    let Some(authenticator) = SmartaccountQuerier::new(querier)
+        .get_authenticator(account.to_string(), root)?
+        .account_authenticator
+    else {
+        return Ok(None);
+    };
+
+    let ChildAuthenticator::<CosmwasmAuthenticatorData> { r#type, data } =
+        authenticator.child_authenticator_data(&path, &CompositeLimits::default())?;
+
+    Ok(
+        (r#type == COSMWASM_AUTHENTICATOR && data.contract == contract.as_str())
+            .then_some(data.params),
+    )
+}
@@ -1,11 +1,13 @@
+use std::str::FromStr;
+
 use cosmwasm_std::{ensure, DepsMut, Env, Response};
 use cw_authenticator::OnAuthenticatorAddedRequest;
 
-use crate::state::EOLS;
+use crate::pause::{ensure_not_paused, PauseScope};
+use crate::state::{CONFIG, EOLS};
 use crate::{
-    authenticator::{handler::validate_and_parse_params, AuthenticatorError},
-    eol::eol::EOL
-    ,
+    authenticator::{handler::validate_and_parse_params, AuthenticatorError, CompositeId},
+    eol::eol::EOL,
 };
 
 pub fn on_authenticator_added(
@@ -17,7 +19,23 @@ pub fn on_authenticator_added(
         authenticator_params,
     }: OnAuthenticatorAddedRequest,
 ) -> Result<Response, AuthenticatorError> {
-    let params = validate_and_parse_params(authenticator_params)?;
+    ensure_not_paused(deps.storage, PauseScope::Registrations)?;
+
+    // the other hooks resolve the id within the composite limits, state kept under
+    // an id out of them could never be used nor cleaned up
+    CompositeId::from_str(&authenticator_id)?;
+
+    let authenticator_params =
+        authenticator_params.ok_or(AuthenticatorError::MissingAuthenticatorParams)?;
+    let params = validate_and_parse_params(Some(authenticator_params))?;
+    params
+        .validate()
+        .map_err(AuthenticatorError::invalid_authenticator_params)?;
+    if let Some(config) = CONFIG.may_load(deps.storage)? {
+        config
+            .ensure_within_bounds(&params)
+            .map_err(AuthenticatorError::invalid_authenticator_params)?;
+    }
 
     // Make sure (account, authenticator_id) is not already present in the state
     let key = (&account, authenticator_id.as_str());
@@ -26,8 +44,12 @@ pub fn on_authenticator_added(
         AuthenticatorError::authenticator_already_exists(account, authenticator_id.as_str())
     );
 
-    // initialize the spending for this authenticator
-    EOLS.save(deps.storage, key, &EOL::new(params.inactivity_period, env.block.time))?;
+    // initialize the spending for this authenticator. It isn't stored on chain until this
+    // hook returns, so the later hooks check the params against its config instead
+    let eol = EOL::new(params.inactivity_period, env.block.time)
+        .with_beneficiary_commitments(params.beneficiary_commitments)
+        .with_cadence(params.cadence);
+    EOLS.save(deps.storage, key, &eol)?;
 
     Ok(Response::new().add_attribute("action", "on_authenticator_added"))
 }
@@ -35,11 +57,22 @@ pub fn on_authenticator_added(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::eol::EOLParams;
-    use cosmwasm_std::{testing::{mock_dependencies_with_balances, mock_env}, to_json_binary, Addr, Coin, StdError, Timestamp};
+    use crate::authenticator::composite::CompositeAuthenticatorError;
+    use crate::config::Config;
+    use crate::eol::cadence::MAX_MISSED_WINDOWS;
+    use crate::eol::{BeneficiaryKey, Cadence, CalendarPeriod, Duration, EOLParams};
+    use crate::pause::PauseStatus;
+    use crate::state::PAUSE_STATUS;
+    use cosmwasm_std::testing::{mock_dependencies, mock_dependencies_with_balances, mock_env};
+    use cosmwasm_std::{to_json_binary, Addr, Binary, Coin, HexBinary, StdError};
+    use rstest::rstest;
 
     const USDC: &str = "ibc/498A0751C798A0D9A389AA3691123DADA57DAA4FE165D5C75894505B876BA6E4";
 
+    fn owner_key() -> Option<BeneficiaryKey> {
+        Some(BeneficiaryKey::Secp256k1(Binary::from(vec![2; 33])))
+    }
+
     #[test]
     fn test_on_authenticator_added() {
         let mut deps = mock_dependencies_with_balances(&[("someoneelse", &[Coin::new(1, USDC)])]);
@@ -70,17 +103,42 @@ mod tests {
             ))
         );
 
-        // valid
+        // registrations paused
+        let mut status = PauseStatus::default();
+        status.set(PauseScope::Registrations, true);
+        PAUSE_STATUS.save(deps.as_mut().storage, &status).unwrap();
         let request = OnAuthenticatorAddedRequest {
             authenticator_id: "2".to_string(),
             account: Addr::unchecked("addr"),
             authenticator_params: Some(
                 to_json_binary(&EOLParams {
-                    inactivity_period: Timestamp::from_seconds(100),
+                    owner_key: owner_key(),
+                    inactivity_period: Duration::from_seconds(100).unwrap(),
+                    ..EOLParams::default()
                 })
-                    .unwrap(),
+                .unwrap(),
             ),
         };
+        assert_eq!(
+            on_authenticator_added(deps.as_mut(), mock_env(), request).unwrap_err(),
+            AuthenticatorError::Paused {
+                scope: PauseScope::Registrations
+            }
+        );
+        PAUSE_STATUS.remove(deps.as_mut().storage);
+
+        // valid
+        let params = to_json_binary(&EOLParams {
+            owner_key: owner_key(),
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            ..EOLParams::default()
+        })
+        .unwrap();
+        let request = OnAuthenticatorAddedRequest {
+            authenticator_id: "2".to_string(),
+            account: Addr::unchecked("addr"),
+            authenticator_params: Some(params.clone()),
+        };
 
         let res = on_authenticator_added(deps.as_mut(), mock_env(), request).unwrap();
         assert_eq!(
@@ -92,7 +150,10 @@ mod tests {
         let spending = EOLS
             .load(deps.as_ref().storage, (&Addr::unchecked("addr"), "2"))
             .unwrap();
-        assert_eq!(spending, EOL::default());
+        assert_eq!(
+            spending,
+            EOL::new(Duration::from_seconds(100).unwrap(), mock_env().block.time)
+        );
 
         // Adding the authenticator with the same (account, authenticator_id) should fail
         let request = OnAuthenticatorAddedRequest {
@@ -100,10 +161,11 @@ mod tests {
             account: Addr::unchecked("addr"),
             authenticator_params: Some(
                 to_json_binary(&EOLParams {
-                    inactivity_period: Timestamp::from_seconds(100),
-
+                    owner_key: owner_key(),
+                    inactivity_period: Duration::from_seconds(100).unwrap(),
+                    ..EOLParams::default()
                 })
-                    .unwrap(),
+                .unwrap(),
             ),
         };
 
@@ -111,5 +173,210 @@ mod tests {
             on_authenticator_added(deps.as_mut(), mock_env(), request).unwrap_err(),
             AuthenticatorError::authenticator_already_exists(Addr::unchecked("addr"), "2")
         );
+
+        // malformed beneficiary key
+        let request = OnAuthenticatorAddedRequest {
+            authenticator_id: "3".to_string(),
+            account: Addr::unchecked("addr"),
+            authenticator_params: Some(
+                to_json_binary(&EOLParams {
+                    owner_key: owner_key(),
+                    inactivity_period: Duration::from_seconds(100).unwrap(),
+                    beneficiaries: vec![BeneficiaryKey::Ed25519(Binary::from(vec![1, 2, 3]))],
+                    ..EOLParams::default()
+                })
+                .unwrap(),
+            ),
+        };
+
+        assert_eq!(
+            on_authenticator_added(deps.as_mut(), mock_env(), request).unwrap_err(),
+            AuthenticatorError::invalid_authenticator_params(StdError::generic_err(
+                "invalid beneficiary public key: AQID"
+            ))
+        );
+
+        // missing owner key
+        let request = OnAuthenticatorAddedRequest {
+            authenticator_id: "3".to_string(),
+            account: Addr::unchecked("addr"),
+            authenticator_params: Some(
+                to_json_binary(&EOLParams {
+                    inactivity_period: Duration::from_seconds(100).unwrap(),
+                    ..EOLParams::default()
+                })
+                .unwrap(),
+            ),
+        };
+
+        assert_eq!(
+            on_authenticator_added(deps.as_mut(), mock_env(), request).unwrap_err(),
+            AuthenticatorError::invalid_authenticator_params(StdError::generic_err(
+                "owner key is required"
+            ))
+        );
+    }
+
+    #[rstest]
+    #[case::root("2", Ok(()))]
+    #[case::nested("2.1023.0", Ok(()))]
+    #[case::too_deep(
+        "2.0.0.0.0.0.0.0.0.0",
+        Err(CompositeAuthenticatorError::CompositeTooDeep { max_depth: 8 })
+    )]
+    #[case::too_wide(
+        "2.1024",
+        Err(CompositeAuthenticatorError::CompositeTooWide { max_width: 1024 })
+    )]
+    fn test_on_authenticator_added_within_composite_limits(
+        #[case] authenticator_id: &str,
+        #[case] expected: Result<(), CompositeAuthenticatorError>,
+    ) {
+        let mut deps = mock_dependencies();
+        let request = OnAuthenticatorAddedRequest {
+            authenticator_id: authenticator_id.to_string(),
+            account: Addr::unchecked("addr"),
+            authenticator_params: Some(
+                to_json_binary(&EOLParams {
+                    owner_key: owner_key(),
+                    inactivity_period: Duration::from_seconds(100).unwrap(),
+                    ..EOLParams::default()
+                })
+                .unwrap(),
+            ),
+        };
+
+        let added = expected.is_ok();
+        assert_eq!(
+            on_authenticator_added(deps.as_mut(), mock_env(), request).map(|_| ()),
+            expected.map_err(AuthenticatorError::from)
+        );
+        assert_eq!(
+            EOLS.has(
+                deps.as_ref().storage,
+                (&Addr::unchecked("addr"), authenticator_id)
+            ),
+            added
+        );
+    }
+
+    #[test]
+    fn test_on_authenticator_added_within_config_bounds() {
+        let mut deps = mock_dependencies();
+        CONFIG
+            .save(
+                deps.as_mut().storage,
+                &Config {
+                    min_inactivity_period: Duration::from_seconds(100).unwrap(),
+                    max_inactivity_period: Duration::from_seconds(1_000).unwrap(),
+                    max_beneficiaries: 1,
+                },
+            )
+            .unwrap();
+        let request = |id: &str, params: EOLParams| OnAuthenticatorAddedRequest {
+            authenticator_id: id.to_string(),
+            account: Addr::unchecked("addr"),
+            authenticator_params: Some(to_json_binary(&params).unwrap()),
+        };
+
+        // too short
+        assert_eq!(
+            on_authenticator_added(
+                deps.as_mut(),
+                mock_env(),
+                request(
+                    "2",
+                    EOLParams {
+                        owner_key: owner_key(),
+                        inactivity_period: Duration::from_seconds(99).unwrap(),
+                        ..EOLParams::default()
+                    }
+                )
+            )
+            .unwrap_err(),
+            AuthenticatorError::invalid_authenticator_params(StdError::generic_err(
+                "inactivity period must be between 100s and 1000s"
+            ))
+        );
+
+        // too many beneficiaries
+        assert_eq!(
+            on_authenticator_added(
+                deps.as_mut(),
+                mock_env(),
+                request(
+                    "2",
+                    EOLParams {
+                        owner_key: owner_key(),
+                        inactivity_period: Duration::from_seconds(100).unwrap(),
+                        beneficiary_commitments: vec![HexBinary::from(vec![0; 32]); 2],
+                        ..EOLParams::default()
+                    }
+                )
+            )
+            .unwrap_err(),
+            AuthenticatorError::invalid_authenticator_params(StdError::generic_err(
+                "at most 1 beneficiaries are allowed"
+            ))
+        );
+
+        // within bounds
+        on_authenticator_added(
+            deps.as_mut(),
+            mock_env(),
+            request(
+                "2",
+                EOLParams {
+                    owner_key: owner_key(),
+                    inactivity_period: Duration::from_seconds(1_000).unwrap(),
+                    beneficiary_commitments: vec![HexBinary::from(vec![0; 32])],
+                    ..EOLParams::default()
+                },
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_on_authenticator_added_with_max_cadence() {
+        let mut deps = mock_dependencies();
+        CONFIG
+            .save(
+                deps.as_mut().storage,
+                &Config {
+                    min_inactivity_period: Duration::from_seconds(0).unwrap(),
+                    max_inactivity_period: Duration::from_nanos(u64::MAX),
+                    max_beneficiaries: 1,
+                },
+            )
+            .unwrap();
+        let request = |missed_windows: u32| OnAuthenticatorAddedRequest {
+            authenticator_id: "2".to_string(),
+            account: Addr::unchecked("addr"),
+            authenticator_params: Some(
+                to_json_binary(&EOLParams {
+                    owner_key: owner_key(),
+                    inactivity_period: Duration::from_seconds(100).unwrap(),
+                    cadence: Some(Cadence {
+                        period: CalendarPeriod::Yearly,
+                        missed_windows,
+                    }),
+                    ..EOLParams::default()
+                })
+                .unwrap(),
+            ),
+        };
+
+        for missed_windows in [MAX_MISSED_WINDOWS + 1, u32::MAX] {
+            assert_eq!(
+                on_authenticator_added(deps.as_mut(), mock_env(), request(missed_windows))
+                    .unwrap_err(),
+                AuthenticatorError::invalid_authenticator_params(StdError::generic_err(format!(
+                    "cadence may allow at most {MAX_MISSED_WINDOWS} missed windows"
+                )))
+            );
+        }
+
+        on_authenticator_added(deps.as_mut(), mock_env(), request(MAX_MISSED_WINDOWS)).unwrap();
     }
 }
@@ -1,8 +1,10 @@
-use cosmwasm_std::{DepsMut, Env, Response};
+use std::str::FromStr;
+
+use cosmwasm_std::{DepsMut, Env, Order, Response, StdResult};
 use cw_authenticator::OnAuthenticatorRemovedRequest;
 
-use crate::state::EOLS;
-use crate::authenticator::AuthenticatorError;
+use crate::authenticator::{AuthenticatorError, CompositeId};
+use crate::state::{eols_under, ASSET_TRANSFERS, EOLS};
 
 pub fn on_authenticator_removed(
     deps: DepsMut,
@@ -13,17 +15,35 @@ pub fn on_authenticator_removed(
         ..
     }: OnAuthenticatorRemovedRequest,
 ) -> Result<Response, AuthenticatorError> {
-    // clean up the spending
-    EOLS.remove(deps.storage, (&account, authenticator_id.as_str()));
+    // clean up the state of the authenticator and of every one nested under it,
+    // removing a root takes its whole composite tree with it
+    let id = CompositeId::from_str(&authenticator_id)?;
+    for (authenticator_id, _) in eols_under(deps.storage, &account, &id)? {
+        EOLS.remove(deps.storage, (&account, authenticator_id.as_str()));
+
+        let beneficiaries = ASSET_TRANSFERS
+            .prefix((&account, authenticator_id.as_str()))
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for beneficiary in beneficiaries {
+            ASSET_TRANSFERS.remove(
+                deps.storage,
+                (&account, authenticator_id.as_str(), beneficiary.as_str()),
+            );
+        }
+    }
 
     Ok(Response::new().add_attribute("action", "on_authenticator_removed"))
 }
 
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::{testing::{mock_dependencies, mock_env}, to_json_binary, Addr, Timestamp};
+    use cosmwasm_std::{
+        testing::{mock_dependencies, mock_env},
+        to_json_binary, Addr,
+    };
 
-    use crate::eol::{EOLParams, EOL};
+    use crate::eol::{AssetTransfers, Duration, EOLParams, EOL};
 
     use super::*;
 
@@ -33,23 +53,52 @@ mod tests {
 
         // remove the spending
         let key = (&Addr::unchecked("account"), "2");
-        EOLS
-            .save(deps.as_mut().storage, key, &EOL::default())
+        EOLS.save(deps.as_mut().storage, key, &EOL::default())
+            .unwrap();
+        assert!(EOLS.has(deps.as_ref().storage, key));
+        let asset_key = (&Addr::unchecked("account"), "2", "beneficiary/0");
+        ASSET_TRANSFERS
+            .save(deps.as_mut().storage, asset_key, &AssetTransfers::default())
             .unwrap();
-        assert!(SPENDINGS.has(deps.as_ref().storage, key));
 
         let msg = OnAuthenticatorRemovedRequest {
             authenticator_id: "2".to_string(),
             account: Addr::unchecked("account"),
             authenticator_params: Some(
                 to_json_binary(&EOLParams {
-                    inactivity_period: Timestamp::from_seconds(100)
+                    inactivity_period: Duration::from_seconds(100).unwrap(),
+                    ..EOLParams::default()
                 })
-                    .unwrap(),
+                .unwrap(),
             ),
         };
 
         on_authenticator_removed(deps.as_mut(), mock_env(), msg).unwrap();
-        assert!(!SPENDINGS.has(deps.as_ref().storage, key));
+        assert!(!EOLS.has(deps.as_ref().storage, key));
+        assert!(!ASSET_TRANSFERS.has(deps.as_ref().storage, asset_key));
+    }
+
+    #[test]
+    fn test_on_authenticator_removed_cleans_descendants() {
+        let mut deps = mock_dependencies();
+        let account = Addr::unchecked("account");
+        for id in ["5", "5.0", "5.1.2", "50", "6"] {
+            EOLS.save(deps.as_mut().storage, (&account, id), &EOL::default())
+                .unwrap();
+        }
+
+        let msg = OnAuthenticatorRemovedRequest {
+            authenticator_id: "5".to_string(),
+            account: account.clone(),
+            authenticator_params: None,
+        };
+        on_authenticator_removed(deps.as_mut(), mock_env(), msg).unwrap();
+
+        let remaining = EOLS
+            .prefix(&account)
+            .keys(deps.as_ref().storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(remaining, vec!["50".to_string(), "6".to_string()]);
     }
 }
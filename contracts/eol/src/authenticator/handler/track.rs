@@ -1,9 +1,11 @@
-use crate::state::EOLS;
+use crate::eol::{fee::paid_by_account, reserve::resolve_reserve};
+use crate::state::{EOLS, SESSIONS};
 use crate::ContractError;
-use cosmwasm_std::{DepsMut, Env, Response};
+use cosmwasm_std::{DepsMut, Env, Order, Response, StdResult};
 use cw_authenticator::TrackRequest;
+use cw_storage_plus::Bound;
 
-use super::validate_and_parse_params;
+use super::verify_and_parse_params;
 
 pub fn track(
     deps: DepsMut,
@@ -14,323 +16,499 @@ pub fn track(
         fee_payer,
         fee_granter,
         fee,
+        msg_index,
         authenticator_params,
         ..
     }: TrackRequest,
 ) -> Result<Response, ContractError> {
-    let params = validate_and_parse_params(authenticator_params)?;
     let key = (&account, authenticator_id.as_str());
+    let params = verify_and_parse_params(
+        &deps.querier,
+        &env.contract.address,
+        &account,
+        &authenticator_id,
+        authenticator_params,
+    )?;
+
+    let session = SESSIONS
+        .may_load(
+            deps.storage,
+            (&account, authenticator_id.as_str(), msg_index),
+        )?
+        .filter(|session| session.is_current(&env.block));
+
+    // only the owner's activity keeps the account alive,
+    // otherwise a beneficiary would push the account out of dormancy
+    // by merely using it
+    // simulations must not move the liveness clock either
+    if session
+        .as_ref()
+        .is_some_and(|session| !session.is_beneficiary() && !session.simulate)
+    {
+        let mut eol = EOLS.load(deps.storage, key)?;
+        eol.update(env.block.time);
+        EOLS.save(deps.storage, key, &eol)?;
+    }
+
+    // the account's fees are charged once per transaction, so they are accounted for
+    // with the first message this authenticator tracks
+    if let Some(budget) = &params.fee_budget {
+        let charged = session
+            .as_ref()
+            .is_some_and(|session| session.is_beneficiary())
+            && paid_by_account(&account, &fee_payer, fee_granter.as_ref())
+            && !SESSIONS
+                .prefix(key)
+                .range(
+                    deps.storage,
+                    None,
+                    Some(Bound::exclusive(msg_index)),
+                    Order::Ascending,
+                )
+                .collect::<StdResult<Vec<_>>>()?
+                .iter()
+                .any(|(_, session)| session.is_current(&env.block));
+
+        // simulations are still checked against the budget, without consuming it
+        if charged {
+            let mut eol = EOLS.load(deps.storage, key)?;
+            eol.fee_spending.spend(budget, &fee, env.block.time)?;
+            if !session.as_ref().is_some_and(|session| session.simulate) {
+                EOLS.save(deps.storage, key, &eol)?;
+            }
+        }
+    }
+
+    // the reserve is resolved against the balances snapshotted before the first
+    // beneficiary transaction, and checked against once the message executed.
+    // Restricted denoms are checked against the balances before this transaction.
+    if let Some(mut session) = session.filter(|session| session.is_beneficiary()) {
+        if !params.reserve.is_empty() {
+            let mut eol = EOLS.load(deps.storage, key)?;
+            let reserved = match &eol.reserved {
+                Some(reserved) => reserved.clone(),
+                None => resolve_reserve(&deps.querier, &account, &params.reserve)?,
+            };
+
+            if eol.reserved.is_none() && !session.simulate {
+                eol.reserved = Some(reserved.clone());
+                EOLS.save(deps.storage, key, &eol)?;
+            }
+
+            session.reserved = reserved;
+        }
 
-    // TODO: only write if original use account is using the smart account
-    // orelse nominee will be blocked for another x duration
-    // unless nominee executes the tx that authenticates another account for full access in the tx.
+        if params.tracks_balances() {
+            session.balances = deps.querier.query_all_balances(&account)?;
+        }
 
-    let mut eol = EOLS.load(deps.storage, key)?;
-    let eol2 = eol.update(env.block.time);
-    EOLS.save(deps.storage, key, &eol2)?;
+        SESSIONS.save(
+            deps.storage,
+            (&account, authenticator_id.as_str(), msg_index),
+            &session,
+        )?;
+    }
 
     Ok(Response::new().add_attribute("action", "track"))
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//
-//     use crate::fee::UntrackedSpentFee;
-//     use crate::period::Period;
-//     use crate::{spend_limit::SpendLimitParams, state::UNTRACKED_SPENT_FEES};
-//     use cosmwasm_std::{
-//         testing::{mock_dependencies_with_balances, mock_env},
-//         to_json_binary, Addr, Binary, Coin, Uint128,
-//     };
-//     use cw_authenticator::TrackRequest;
-//
-//     #[test]
-//     fn test_track_success() {
-//         let mut deps = mock_dependencies_with_balances(&[("addr", &[Coin::new(1000, "uusdc")])]);
-//         let fee = vec![Coin::new(1000, "uosmo"), Coin::new(1000, "usdc")];
-//         let track_request = TrackRequest {
-//             authenticator_id: "2".to_string(),
-//             account: Addr::unchecked("addr"),
-//             fee_payer: Addr::unchecked("addr"),
-//             fee_granter: None,
-//             fee: fee.clone(),
-//             authenticator_params: Some(
-//                 to_json_binary(&SpendLimitParams {
-//                     limit: Uint128::new(500_000_000),
-//                     reset_period: Period::Day,
-//                     time_limit: None,
-//                 })
-//                     .unwrap(),
-//             ),
-//             msg: cw_authenticator::Any {
-//                 type_url: "".to_string(),
-//                 value: Binary::default(),
-//             },
-//             msg_index: 0,
-//         };
-//
-//         let response = track(deps.as_mut(), mock_env(), track_request).unwrap();
-//         assert_eq!(response, Response::new().add_attribute("action", "track"));
-//
-//         // Verify that the pre_exec_balance is updated
-//         let key = (&Addr::unchecked("addr"), "2");
-//         let pre_exec_balance = PRE_EXEC_BALANCES.load(deps.as_ref().storage, key).unwrap();
-//         assert_eq!(pre_exec_balance, vec![Coin::new(1000, "uusdc")]);
-//
-//         let untracked_spent_fee = UNTRACKED_SPENT_FEES
-//             .load(deps.as_ref().storage, key)
-//             .unwrap_or_default();
-//         assert_eq!(
-//             untracked_spent_fee,
-//             UntrackedSpentFee {
-//                 fee,
-//                 updated_at: mock_env().block.time,
-//             }
-//         );
-//     }
-//
-//     #[test]
-//     fn test_track_success_with_dirty_pre_exec_balance() {
-//         let mut deps = mock_dependencies_with_balances(&[("addr", &[Coin::new(1000, "uusdc")])]);
-//
-//         let key = (&Addr::unchecked("addr"), "2");
-//
-//         // make sure the pre-exec balance dirty
-//         PRE_EXEC_BALANCES
-//             .save(deps.as_mut().storage, key, &vec![Coin::new(500, "uusdc")])
-//             .unwrap();
-//
-//         let fee = vec![Coin::new(1000, "uosmo"), Coin::new(1000, "usdc")];
-//         let track_request = TrackRequest {
-//             authenticator_id: "2".to_string(),
-//             account: Addr::unchecked("addr"),
-//             fee_payer: Addr::unchecked("addr"),
-//             fee_granter: None,
-//             fee: fee.clone(),
-//             authenticator_params: Some(
-//                 to_json_binary(&SpendLimitParams {
-//                     limit: Uint128::new(500_000_000),
-//                     reset_period: Period::Day,
-//                     time_limit: None,
-//                 })
-//                     .unwrap(),
-//             ),
-//             msg: cw_authenticator::Any {
-//                 type_url: "".to_string(),
-//                 value: Binary::default(),
-//             },
-//             msg_index: 0,
-//         };
-//
-//         let response = track(deps.as_mut(), mock_env(), track_request).unwrap();
-//         assert_eq!(response, Response::new().add_attribute("action", "track"));
-//
-//         // Verify that the pre_exec_balance is updated
-//         let pre_exec_balance = PRE_EXEC_BALANCES.load(deps.as_ref().storage, key).unwrap();
-//         assert_eq!(pre_exec_balance, vec![Coin::new(1000, "uusdc")]);
-//
-//         let untracked_spent_fee = UNTRACKED_SPENT_FEES
-//             .load(deps.as_ref().storage, key)
-//             .unwrap_or_default();
-//         assert_eq!(
-//             untracked_spent_fee,
-//             UntrackedSpentFee {
-//                 fee,
-//                 updated_at: mock_env().block.time,
-//             }
-//         );
-//     }
-//
-//     #[test]
-//     fn test_track_success_with_dirty_untracked_fees() {
-//         let mut deps = mock_dependencies_with_balances(&[("addr", &[Coin::new(1000, "uusdc")])]);
-//
-//         let key = (&Addr::unchecked("addr"), "2");
-//
-//         let prev_untracked_spent_fee = vec![Coin::new(500, "uosmo")];
-//
-//         UNTRACKED_SPENT_FEES
-//             .save(
-//                 deps.as_mut().storage,
-//                 key,
-//                 &UntrackedSpentFee {
-//                     fee: prev_untracked_spent_fee,
-//                     updated_at: mock_env().block.time,
-//                 },
-//             )
-//             .unwrap();
-//
-//         let fee = vec![Coin::new(1000, "uosmo"), Coin::new(1000, "usdc")];
-//
-//         let track_request = TrackRequest {
-//             authenticator_id: "2".to_string(),
-//             account: Addr::unchecked("addr"),
-//             fee_payer: Addr::unchecked("addr"),
-//             fee_granter: None,
-//             fee: fee.clone(),
-//             authenticator_params: Some(
-//                 to_json_binary(&SpendLimitParams {
-//                     limit: Uint128::new(500_000_000),
-//                     reset_period: Period::Day,
-//                     time_limit: None,
-//                 })
-//                     .unwrap(),
-//             ),
-//             msg: cw_authenticator::Any {
-//                 type_url: "".to_string(),
-//                 value: Binary::default(),
-//             },
-//             msg_index: 0,
-//         };
-//
-//         let response = track(deps.as_mut(), mock_env(), track_request).unwrap();
-//         assert_eq!(response, Response::new().add_attribute("action", "track"));
-//
-//         // Verify that the pre_exec_balance is updated
-//         let key = (&Addr::unchecked("addr"), "2");
-//         let pre_exec_balance = PRE_EXEC_BALANCES.load(deps.as_ref().storage, key).unwrap();
-//         assert_eq!(pre_exec_balance, vec![Coin::new(1000, "uusdc")]);
-//
-//         // Verify that the untracked spent fee is updated
-//         let untracked_spent_fee = UNTRACKED_SPENT_FEES
-//             .load(deps.as_ref().storage, key)
-//             .unwrap_or_default();
-//
-//         assert_eq!(
-//             untracked_spent_fee,
-//             UntrackedSpentFee {
-//                 fee: vec![Coin::new(1500, "uosmo"), Coin::new(1000, "usdc")],
-//                 updated_at: mock_env().block.time,
-//             }
-//         );
-//     }
-//
-//     #[test]
-//     fn test_track_success_not_accum_fee_if_has_fee_granter() {
-//         let mut deps = mock_dependencies_with_balances(&[("addr", &[Coin::new(1000, "uusdc")])]);
-//
-//         let key = (&Addr::unchecked("addr"), "2");
-//
-//         let prev_untracked_spent_fee = vec![Coin::new(500, "uosmo")];
-//
-//         UNTRACKED_SPENT_FEES
-//             .save(
-//                 deps.as_mut().storage,
-//                 key,
-//                 &UntrackedSpentFee {
-//                     fee: prev_untracked_spent_fee.clone(),
-//                     updated_at: mock_env().block.time,
-//                 },
-//             )
-//             .unwrap();
-//
-//         let fee = vec![Coin::new(1000, "uosmo"), Coin::new(1000, "usdc")];
-//
-//         let track_request = TrackRequest {
-//             authenticator_id: "2".to_string(),
-//             account: Addr::unchecked("addr"),
-//             fee_payer: Addr::unchecked("addr"),
-//             fee_granter: Some(Addr::unchecked("granter")),
-//             fee: fee.clone(),
-//             authenticator_params: Some(
-//                 to_json_binary(&SpendLimitParams {
-//                     limit: Uint128::new(500_000_000),
-//                     reset_period: Period::Day,
-//                     time_limit: None,
-//                 })
-//                     .unwrap(),
-//             ),
-//             msg: cw_authenticator::Any {
-//                 type_url: "".to_string(),
-//                 value: Binary::default(),
-//             },
-//             msg_index: 0,
-//         };
-//
-//         let response = track(deps.as_mut(), mock_env(), track_request).unwrap();
-//         assert_eq!(response, Response::new().add_attribute("action", "track"));
-//
-//         // Verify that the pre_exec_balance is updated
-//         let key = (&Addr::unchecked("addr"), "2");
-//         let pre_exec_balance = PRE_EXEC_BALANCES.load(deps.as_ref().storage, key).unwrap();
-//         assert_eq!(pre_exec_balance, vec![Coin::new(1000, "uusdc")]);
-//
-//         // Verify that the untracked spent fee is updated
-//         let untracked_spent_fee = UNTRACKED_SPENT_FEES
-//             .load(deps.as_ref().storage, key)
-//             .unwrap_or_default();
-//
-//         assert_eq!(
-//             untracked_spent_fee,
-//             UntrackedSpentFee {
-//                 fee: prev_untracked_spent_fee,
-//                 updated_at: mock_env().block.time,
-//             }
-//         );
-//     }
-//
-//     #[test]
-//     fn test_track_success_not_accum_fee_if_not_a_fee_payer() {
-//         let mut deps = mock_dependencies_with_balances(&[("addr", &[Coin::new(1000, "uusdc")])]);
-//
-//         let key = (&Addr::unchecked("addr"), "2");
-//
-//         let prev_untracked_spent_fee = vec![Coin::new(500, "uosmo")];
-//
-//         UNTRACKED_SPENT_FEES
-//             .save(
-//                 deps.as_mut().storage,
-//                 key,
-//                 &UntrackedSpentFee {
-//                     fee: prev_untracked_spent_fee.clone(),
-//                     updated_at: mock_env().block.time,
-//                 },
-//             )
-//             .unwrap();
-//
-//         let fee = vec![Coin::new(1000, "uosmo"), Coin::new(1000, "usdc")];
-//
-//         let track_request = TrackRequest {
-//             authenticator_id: "2".to_string(),
-//             account: Addr::unchecked("addr"),
-//             fee_payer: Addr::unchecked("not_addr"),
-//             fee_granter: None,
-//             fee: fee.clone(),
-//             authenticator_params: Some(
-//                 to_json_binary(&SpendLimitParams {
-//                     limit: Uint128::new(500_000_000),
-//                     reset_period: Period::Day,
-//                     time_limit: None,
-//                 })
-//                     .unwrap(),
-//             ),
-//             msg: cw_authenticator::Any {
-//                 type_url: "".to_string(),
-//                 value: Binary::default(),
-//             },
-//             msg_index: 0,
-//         };
-//
-//         let response = track(deps.as_mut(), mock_env(), track_request).unwrap();
-//         assert_eq!(response, Response::new().add_attribute("action", "track"));
-//
-//         // Verify that the pre_exec_balance is updated
-//         let key = (&Addr::unchecked("addr"), "2");
-//         let pre_exec_balance = PRE_EXEC_BALANCES.load(deps.as_ref().storage, key).unwrap();
-//         assert_eq!(pre_exec_balance, vec![Coin::new(1000, "uusdc")]);
-//
-//         // Verify that the untracked spent fee is updated
-//         let untracked_spent_fee = UNTRACKED_SPENT_FEES
-//             .load(deps.as_ref().storage, key)
-//             .unwrap_or_default();
-//
-//         assert_eq!(
-//             untracked_spent_fee,
-//             UntrackedSpentFee {
-//                 fee: prev_untracked_spent_fee,
-//                 updated_at: mock_env().block.time,
-//             }
-//         );
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authenticator::AuthenticatorError;
+    use crate::eol::{Duration, EOLParams, FeeBudget, FeeSpending, Reserve, Session, Signer, EOL};
+    use crate::test_helper::mock_stargate_querier::{
+        mock_dependencies_with_registered_params, mock_dependencies_with_stargate_querier,
+        registered_params_query_handler,
+    };
+    use cosmwasm_std::{
+        coin, coins,
+        testing::{mock_env, MOCK_CONTRACT_ADDR},
+        to_json_binary, Addr, Binary, Decimal, Timestamp,
+    };
+    use cw_authenticator::Any;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::no_session(None, false)]
+    #[case::owner(Some((Signer::Owner, 0)), true)]
+    #[case::beneficiary(Some((Signer::Beneficiary { index: 0 }, 0)), false)]
+    #[case::stale_owner_session(Some((Signer::Owner, 1)), false)]
+    fn test_track_updates_liveness(
+        #[case] session: Option<(Signer, u64)>,
+        #[case] expected_update: bool,
+    ) {
+        let params = to_json_binary(&EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            ..EOLParams::default()
+        })
+        .unwrap();
+        let mut deps = mock_dependencies_with_registered_params(&[], &params);
+        let mut env = mock_env();
+        let key = (&Addr::unchecked("account"), "2");
+        let eol = EOL::new(Duration::from_seconds(100).unwrap(), env.block.time);
+        EOLS.save(deps.as_mut().storage, key, &eol).unwrap();
+
+        if let Some((signer, blocks_ago)) = session {
+            let mut block = env.block.clone();
+            block.height -= blocks_ago;
+            SESSIONS
+                .save(
+                    deps.as_mut().storage,
+                    (&Addr::unchecked("account"), "2", 0),
+                    &Session::new(signer, &block),
+                )
+                .unwrap();
+        }
+
+        env.block.time = env.block.time.plus_seconds(50);
+        track(
+            deps.as_mut(),
+            env.clone(),
+            TrackRequest {
+                account: Addr::unchecked("account"),
+                fee_payer: Addr::unchecked("account"),
+                fee_granter: None,
+                fee: vec![],
+                authenticator_id: "2".to_string(),
+                msg: Any {
+                    type_url: "".to_string(),
+                    value: Binary::default(),
+                },
+                msg_index: 0,
+                authenticator_params: Some(params),
+            },
+        )
+        .unwrap();
+
+        let last_spent_at = EOLS.load(deps.as_ref().storage, key).unwrap().last_spent_at;
+        if expected_update {
+            assert_eq!(last_spent_at, env.block.time);
+        } else {
+            assert_eq!(last_spent_at, eol.last_spent_at);
+        }
+    }
+
+    #[test]
+    fn test_track_fee_spending() {
+        let params = to_json_binary(&EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            fee_budget: Some(FeeBudget {
+                limit: coins(1_000, "uosmo"),
+                period: Duration::from_seconds(100).unwrap(),
+            }),
+            ..EOLParams::default()
+        })
+        .unwrap();
+        let mut deps = mock_dependencies_with_registered_params(&[], &params);
+        let env = mock_env();
+        let account = Addr::unchecked("account");
+        let key = (&account, "2");
+        EOLS.save(
+            deps.as_mut().storage,
+            key,
+            &EOL::new(
+                Duration::from_seconds(100).unwrap(),
+                Timestamp::from_seconds(0),
+            ),
+        )
+        .unwrap();
+
+        for msg_index in 0..2 {
+            SESSIONS
+                .save(
+                    deps.as_mut().storage,
+                    (&account, "2", msg_index),
+                    &Session::new(Signer::Beneficiary { index: 0 }, &env.block),
+                )
+                .unwrap();
+        }
+
+        let request = |msg_index: u64, fee_granter: Option<&str>| TrackRequest {
+            account: account.clone(),
+            fee_payer: account.clone(),
+            fee_granter: fee_granter.map(Addr::unchecked),
+            fee: coins(300, "uosmo"),
+            authenticator_id: "2".to_string(),
+            msg: Any {
+                type_url: "".to_string(),
+                value: Binary::default(),
+            },
+            msg_index,
+            authenticator_params: Some(params.clone()),
+        };
+
+        // the fee is charged once for the whole transaction
+        track(deps.as_mut(), env.clone(), request(0, None)).unwrap();
+        track(deps.as_mut(), env.clone(), request(1, None)).unwrap();
+        assert_eq!(
+            EOLS.load(deps.as_ref().storage, key).unwrap().fee_spending,
+            FeeSpending {
+                period_start: env.block.time,
+                spent: coins(300, "uosmo"),
+            }
+        );
+
+        // granted fees are not paid by the account
+        track(deps.as_mut(), env.clone(), request(0, Some("granter"))).unwrap();
+        assert_eq!(
+            EOLS.load(deps.as_ref().storage, key)
+                .unwrap()
+                .fee_spending
+                .spent,
+            coins(300, "uosmo")
+        );
+    }
+
+    #[test]
+    fn test_track_simulation() {
+        let params = to_json_binary(&EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            fee_budget: Some(FeeBudget {
+                limit: coins(1_000, "uosmo"),
+                period: Duration::from_seconds(100).unwrap(),
+            }),
+            ..EOLParams::default()
+        })
+        .unwrap();
+        let mut deps = mock_dependencies_with_registered_params(&[], &params);
+        let env = mock_env();
+        let account = Addr::unchecked("account");
+        let key = (&account, "2");
+        let eol = EOL::new(
+            Duration::from_seconds(100).unwrap(),
+            Timestamp::from_seconds(0),
+        );
+        EOLS.save(deps.as_mut().storage, key, &eol).unwrap();
+
+        let request = |msg_index: u64, fee: u128| TrackRequest {
+            account: account.clone(),
+            fee_payer: account.clone(),
+            fee_granter: None,
+            fee: coins(fee, "uosmo"),
+            authenticator_id: "2".to_string(),
+            msg: Any {
+                type_url: "".to_string(),
+                value: Binary::default(),
+            },
+            msg_index,
+            authenticator_params: Some(params.clone()),
+        };
+
+        // simulated owner activity doesn't keep the account alive
+        SESSIONS
+            .save(
+                deps.as_mut().storage,
+                (&account, "2", 0),
+                &Session::new(Signer::Owner, &env.block).with_simulate(true),
+            )
+            .unwrap();
+        track(deps.as_mut(), env.clone(), request(0, 300)).unwrap();
+
+        // simulated beneficiary transactions don't consume the fee budget,
+        // yet are still checked against it
+        SESSIONS
+            .save(
+                deps.as_mut().storage,
+                (&account, "2", 1),
+                &Session::new(Signer::Beneficiary { index: 0 }, &env.block).with_simulate(true),
+            )
+            .unwrap();
+        SESSIONS.remove(deps.as_mut().storage, (&account, "2", 0));
+        track(deps.as_mut(), env.clone(), request(1, 300)).unwrap();
+        assert!(track(deps.as_mut(), env.clone(), request(1, 1_001)).is_err());
+
+        assert_eq!(EOLS.load(deps.as_ref().storage, key).unwrap(), eol);
+    }
+
+    #[test]
+    fn test_track_resolves_reserve() {
+        let params = to_json_binary(&EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            reserve: vec![Reserve::Ratio {
+                denom: "uosmo".to_string(),
+                ratio: Decimal::percent(10),
+            }],
+            ..EOLParams::default()
+        })
+        .unwrap();
+        let mut deps = mock_dependencies_with_registered_params(&coins(1_000, "uosmo"), &params);
+        let env = mock_env();
+        let account = env.contract.address.clone();
+        let key = (&account, "2");
+        EOLS.save(
+            deps.as_mut().storage,
+            key,
+            &EOL::new(
+                Duration::from_seconds(100).unwrap(),
+                Timestamp::from_seconds(0),
+            ),
+        )
+        .unwrap();
+
+        let request = |msg_index: u64| TrackRequest {
+            account: account.clone(),
+            fee_payer: account.clone(),
+            fee_granter: None,
+            fee: vec![],
+            authenticator_id: "2".to_string(),
+            msg: Any {
+                type_url: "".to_string(),
+                value: Binary::default(),
+            },
+            msg_index,
+            authenticator_params: Some(params.clone()),
+        };
+        let save_session = |deps: &mut DepsMut, msg_index: u64| {
+            SESSIONS
+                .save(
+                    deps.storage,
+                    (&account, "2", msg_index),
+                    &Session::new(Signer::Beneficiary { index: 0 }, &env.block),
+                )
+                .unwrap();
+        };
+
+        save_session(&mut deps.as_mut(), 0);
+        track(deps.as_mut(), env.clone(), request(0)).unwrap();
+        assert_eq!(
+            EOLS.load(deps.as_ref().storage, key).unwrap().reserved,
+            Some(coins(100, "uosmo"))
+        );
+        assert_eq!(
+            SESSIONS
+                .load(deps.as_ref().storage, (&account, "2", 0))
+                .unwrap()
+                .reserved,
+            coins(100, "uosmo")
+        );
+
+        // the reserve stays what it was at the first snapshot
+        deps.querier
+            .update_balance(account.clone(), coins(500, "uosmo"));
+        save_session(&mut deps.as_mut(), 1);
+        track(deps.as_mut(), env.clone(), request(1)).unwrap();
+        assert_eq!(
+            SESSIONS
+                .load(deps.as_ref().storage, (&account, "2", 1))
+                .unwrap()
+                .reserved,
+            coins(100, "uosmo")
+        );
+    }
+
+    #[test]
+    fn test_track_snapshots_balances() {
+        let params = to_json_binary(&EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            denied_denoms: vec!["ugov".to_string()],
+            ..EOLParams::default()
+        })
+        .unwrap();
+        let mut deps = mock_dependencies_with_registered_params(
+            &[coin(100, "ugov"), coin(100, "uosmo")],
+            &params,
+        );
+        let env = mock_env();
+        let account = env.contract.address.clone();
+        EOLS.save(
+            deps.as_mut().storage,
+            (&account, "2"),
+            &EOL::new(
+                Duration::from_seconds(100).unwrap(),
+                Timestamp::from_seconds(0),
+            ),
+        )
+        .unwrap();
+        SESSIONS
+            .save(
+                deps.as_mut().storage,
+                (&account, "2", 0),
+                &Session::new(Signer::Beneficiary { index: 0 }, &env.block),
+            )
+            .unwrap();
+
+        track(
+            deps.as_mut(),
+            env.clone(),
+            TrackRequest {
+                account: account.clone(),
+                fee_payer: account.clone(),
+                fee_granter: None,
+                fee: vec![],
+                authenticator_id: "2".to_string(),
+                msg: Any {
+                    type_url: "".to_string(),
+                    value: Binary::default(),
+                },
+                msg_index: 0,
+                authenticator_params: Some(params),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            SESSIONS
+                .load(deps.as_ref().storage, (&account, "2", 0))
+                .unwrap()
+                .balances,
+            vec![coin(100, "ugov"), coin(100, "uosmo")]
+        );
+    }
+
+    #[rstest]
+    #[case::registered(MOCK_CONTRACT_ADDR, 100, true)]
+    #[case::other_params(MOCK_CONTRACT_ADDR, 1, false)]
+    #[case::other_contract("other_contract", 100, false)]
+    fn test_track_rejects_unregistered_params(
+        #[case] registered_contract: &str,
+        #[case] inactivity_period: u64,
+        #[case] expected_ok: bool,
+    ) {
+        let params = |inactivity_period: u64| {
+            to_json_binary(&EOLParams {
+                inactivity_period: Duration::from_seconds(inactivity_period).unwrap(),
+                ..EOLParams::default()
+            })
+            .unwrap()
+        };
+        let mut deps = mock_dependencies_with_stargate_querier(
+            &[],
+            registered_params_query_handler(registered_contract, &params(100)),
+        );
+        let env = mock_env();
+        let account = Addr::unchecked("account");
+        EOLS.save(
+            deps.as_mut().storage,
+            (&account, "2"),
+            &EOL::new(Duration::from_seconds(100).unwrap(), env.block.time),
+        )
+        .unwrap();
+
+        let res = track(
+            deps.as_mut(),
+            env,
+            TrackRequest {
+                account: account.clone(),
+                fee_payer: account.clone(),
+                fee_granter: None,
+                fee: vec![],
+                authenticator_id: "2".to_string(),
+                msg: Any {
+                    type_url: "".to_string(),
+                    value: Binary::default(),
+                },
+                msg_index: 0,
+                authenticator_params: Some(params(inactivity_period)),
+            },
+        );
+        if expected_ok {
+            res.unwrap();
+        } else {
+            assert_eq!(
+                res.unwrap_err(),
+                AuthenticatorError::params_mismatch(account, "2").into()
+            );
+        }
+    }
+}
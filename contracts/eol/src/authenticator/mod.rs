@@ -1,17 +1,20 @@
 mod composite;
 mod error;
 mod handler;
+mod tree;
 
 use handler::*;
 
 pub use {
     authenticate::authenticate,
     composite::{
-        CompositeAuthenticator, CompositeId, CosmwasmAuthenticatorData, SubAuthenticatorData,
+        ChildAuthenticator, CompositeAuthenticator, CompositeId, CompositeLimits,
+        CosmwasmAuthenticatorData, SubAuthenticatorData, COSMWASM_AUTHENTICATOR,
     },
     confirm_execution::confirm_execution,
     error::AuthenticatorError,
     on_authenticator_added::on_authenticator_added,
     on_authenticator_removed::on_authenticator_removed,
     track::track,
+    tree::{authenticator_tree, AuthenticatorNode},
 };
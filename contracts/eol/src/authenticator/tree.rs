@@ -0,0 +1,234 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{from_json, Addr};
+use osmosis_std::types::osmosis::smartaccount::v1beta1::AccountAuthenticator;
+
+use super::composite::{
+    sub_authenticators, CompositeId, CompositeLimits, CosmwasmAuthenticatorData,
+    COSMWASM_AUTHENTICATOR,
+};
+use super::AuthenticatorError;
+use crate::eol::EOLParams;
+
+/// Authenticator types whose config lists sub-authenticators
+const COMPOSITE_TYPES: [&str; 4] = ["AllOf", "AnyOf", "PartitionedAllOf", "PartitionedAnyOf"];
+
+/// Decoded authenticator, with its sub-authenticators when composite
+#[cw_serde]
+pub struct AuthenticatorNode {
+    pub r#type: String,
+
+    /// Composite id of the authenticator, as passed to the hooks of cosmwasm authenticators
+    pub id: String,
+
+    /// Contract of a cosmwasm authenticator
+    pub contract: Option<String>,
+
+    /// Params of a cosmwasm authenticator referencing this contract
+    pub eol_params: Option<EOLParams>,
+
+    pub children: Vec<AuthenticatorNode>,
+}
+
+/// Decode `authenticator` recursively within `limits`, resolving the params
+/// of the cosmwasm authenticators referencing `contract` into EOL params
+pub fn authenticator_tree(
+    authenticator: AccountAuthenticator,
+    contract: &Addr,
+    limits: &CompositeLimits,
+) -> Result<AuthenticatorNode, AuthenticatorError> {
+    decode_node(
+        authenticator.r#type,
+        &authenticator.config,
+        CompositeId::new(authenticator.id, vec![]),
+        contract,
+        limits,
+    )
+}
+
+impl AuthenticatorNode {
+    /// Node at `path` below this one
+    pub fn descendant(self, path: &[usize]) -> Option<AuthenticatorNode> {
+        path.iter()
+            .try_fold(self, |node, &index| node.children.into_iter().nth(index))
+    }
+}
+
+fn decode_node(
+    r#type: String,
+    config: &[u8],
+    id: CompositeId,
+    contract: &Addr,
+    limits: &CompositeLimits,
+) -> Result<AuthenticatorNode, AuthenticatorError> {
+    limits.ensure_depth(id.path.len())?;
+
+    let mut node = AuthenticatorNode {
+        r#type,
+        id: id.to_string(),
+        contract: None,
+        eol_params: None,
+        children: vec![],
+    };
+
+    if COMPOSITE_TYPES.contains(&node.r#type.as_str()) {
+        for (index, sub_auth) in sub_authenticators(config, limits)?.into_iter().enumerate() {
+            let mut path = id.path.clone();
+            path.push(index);
+            node.children.push(decode_node(
+                sub_auth.r#type,
+                &sub_auth.config,
+                CompositeId::new(id.root, path),
+                contract,
+                limits,
+            )?);
+        }
+    } else if node.r#type == COSMWASM_AUTHENTICATOR {
+        let data: CosmwasmAuthenticatorData = from_json(config)?;
+        if data.contract == contract.as_str() {
+            node.eol_params = Some(
+                from_json(&data.params)
+                    .map_err(AuthenticatorError::invalid_authenticator_params)?,
+            );
+        }
+        node.contract = Some(data.contract);
+    }
+
+    Ok(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authenticator::composite::{CompositeAuthenticatorError, SubAuthenticatorData};
+    use crate::eol::Duration;
+    use cosmwasm_std::to_json_vec;
+
+    fn sub_auth(r#type: &str, config: Vec<u8>) -> SubAuthenticatorData {
+        SubAuthenticatorData {
+            r#type: r#type.to_string(),
+            config,
+        }
+    }
+
+    fn cosmwasm(contract: &str, params: &[u8]) -> Vec<u8> {
+        to_json_vec(&CosmwasmAuthenticatorData {
+            contract: contract.to_string(),
+            params: params.to_vec(),
+        })
+        .unwrap()
+    }
+
+    fn leaf(r#type: &str, id: &str, contract: Option<&str>) -> AuthenticatorNode {
+        AuthenticatorNode {
+            r#type: r#type.to_string(),
+            id: id.to_string(),
+            contract: contract.map(str::to_string),
+            eol_params: None,
+            children: vec![],
+        }
+    }
+
+    #[test]
+    fn test_authenticator_tree() {
+        let params = EOLParams {
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            ..EOLParams::default()
+        };
+        let authenticator = AccountAuthenticator {
+            id: 5,
+            r#type: "AllOf".to_string(),
+            config: to_json_vec(&vec![
+                sub_auth("SignatureVerification", vec![2; 33]),
+                sub_auth(
+                    "AnyOf",
+                    to_json_vec(&vec![
+                        sub_auth(
+                            COSMWASM_AUTHENTICATOR,
+                            cosmwasm("eol", &to_json_vec(&params).unwrap()),
+                        ),
+                        sub_auth(COSMWASM_AUTHENTICATOR, cosmwasm("spend_limit", b"{}")),
+                    ])
+                    .unwrap(),
+                ),
+            ])
+            .unwrap(),
+        };
+
+        let tree = authenticator_tree(
+            authenticator.clone(),
+            &Addr::unchecked("eol"),
+            &CompositeLimits::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            tree,
+            AuthenticatorNode {
+                r#type: "AllOf".to_string(),
+                id: "5".to_string(),
+                contract: None,
+                eol_params: None,
+                children: vec![
+                    leaf("SignatureVerification", "5.0", None),
+                    AuthenticatorNode {
+                        r#type: "AnyOf".to_string(),
+                        id: "5.1".to_string(),
+                        contract: None,
+                        eol_params: None,
+                        children: vec![
+                            AuthenticatorNode {
+                                eol_params: Some(params),
+                                ..leaf(COSMWASM_AUTHENTICATOR, "5.1.0", Some("eol"))
+                            },
+                            leaf(COSMWASM_AUTHENTICATOR, "5.1.1", Some("spend_limit")),
+                        ],
+                    },
+                ],
+            }
+        );
+
+        assert_eq!(
+            tree.clone().descendant(&[1, 1]),
+            Some(leaf(COSMWASM_AUTHENTICATOR, "5.1.1", Some("spend_limit")))
+        );
+        assert_eq!(tree.descendant(&[2]), None);
+
+        let limits = CompositeLimits {
+            max_depth: 1,
+            max_width: 2,
+        };
+        assert_eq!(
+            authenticator_tree(authenticator, &Addr::unchecked("eol"), &limits).unwrap_err(),
+            CompositeAuthenticatorError::CompositeTooDeep { max_depth: 1 }.into()
+        );
+    }
+
+    #[test]
+    fn test_authenticator_tree_rejects_malformed_composite() {
+        let authenticator = AccountAuthenticator {
+            id: 1,
+            r#type: "AllOf".to_string(),
+            config: b"not json".to_vec(),
+        };
+
+        assert!(authenticator_tree(
+            authenticator,
+            &Addr::unchecked("eol"),
+            &CompositeLimits::default()
+        )
+        .is_err());
+        // too wide is caught before the sub-authenticators get decoded
+        let authenticator = AccountAuthenticator {
+            id: 1,
+            r#type: "AllOf".to_string(),
+            config: b"[0, 1, 2]".to_vec(),
+        };
+        let limits = CompositeLimits {
+            max_depth: 1,
+            max_width: 2,
+        };
+        assert_eq!(
+            authenticator_tree(authenticator, &Addr::unchecked("eol"), &limits).unwrap_err(),
+            CompositeAuthenticatorError::CompositeTooWide { max_width: 2 }.into()
+        );
+    }
+}
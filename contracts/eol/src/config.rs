@@ -0,0 +1,137 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ensure, StdError, StdResult};
+
+use crate::eol::{Duration, EOLParams};
+
+/// Bounds every EOL authenticator is held to when added, managed by the admin
+#[cw_serde]
+pub struct Config {
+    /// Shortest inactivity period, below which the account is all but shared with beneficiaries
+    pub min_inactivity_period: Duration,
+
+    /// Longest inactivity period, above which the account never goes dormant in practice
+    pub max_inactivity_period: Duration,
+
+    /// Most beneficiaries, keys and commitments combined
+    pub max_beneficiaries: u32,
+}
+
+impl Config {
+    pub fn validate(&self) -> StdResult<()> {
+        ensure!(
+            self.min_inactivity_period <= self.max_inactivity_period,
+            StdError::generic_err("min inactivity period exceeds max inactivity period")
+        );
+
+        Ok(())
+    }
+
+    /// Ensure `params` stay within the bounds, a cadence by the shortest
+    /// and longest it may take for the account to go dormant
+    pub fn ensure_within_bounds(&self, params: &EOLParams) -> StdResult<()> {
+        let (shortest, longest) = match &params.cadence {
+            Some(cadence) => cadence.bounds()?,
+            None => (params.inactivity_period, params.inactivity_period),
+        };
+        ensure!(
+            shortest >= self.min_inactivity_period && longest <= self.max_inactivity_period,
+            StdError::generic_err(format!(
+                "inactivity period must be between {} and {}",
+                self.min_inactivity_period, self.max_inactivity_period
+            ))
+        );
+
+        let beneficiaries = params.beneficiaries.len() + params.beneficiary_commitments.len();
+        ensure!(
+            beneficiaries <= self.max_beneficiaries as usize,
+            StdError::generic_err(format!(
+                "at most {} beneficiaries are allowed",
+                self.max_beneficiaries
+            ))
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eol::{BeneficiaryKey, Cadence, CalendarPeriod};
+    use cosmwasm_std::{Binary, HexBinary};
+    use rstest::rstest;
+
+    fn config() -> Config {
+        Config {
+            min_inactivity_period: Duration::from_seconds(100).unwrap(),
+            max_inactivity_period: Duration::from_seconds(1_000).unwrap(),
+            max_beneficiaries: 2,
+        }
+    }
+
+    #[rstest]
+    #[case::min(100, 2, true)]
+    #[case::max(1_000, 2, true)]
+    #[case::too_short(99, 0, false)]
+    #[case::too_long(1_001, 0, false)]
+    #[case::too_many_beneficiaries(100, 3, false)]
+    fn test_ensure_within_bounds(
+        #[case] inactivity_period: u64,
+        #[case] beneficiaries: usize,
+        #[case] allowed: bool,
+    ) {
+        // one key, the rest as commitments
+        let params = EOLParams {
+            inactivity_period: Duration::from_seconds(inactivity_period).unwrap(),
+            beneficiaries: vec![BeneficiaryKey::Secp256k1(Binary::from(vec![2; 33]))]
+                .into_iter()
+                .take(beneficiaries)
+                .collect(),
+            beneficiary_commitments: vec![
+                HexBinary::from(vec![0; 32]);
+                beneficiaries.saturating_sub(1)
+            ],
+            ..EOLParams::default()
+        };
+
+        assert_eq!(config().ensure_within_bounds(&params).is_ok(), allowed);
+    }
+
+    #[rstest]
+    #[case::within(CalendarPeriod::Monthly, 1, 1_000_000_000, true)]
+    #[case::monthly_too_long(CalendarPeriod::Monthly, 1, 2_500_000, false)]
+    #[case::yearly_too_long(CalendarPeriod::Yearly, 1, 40_000_000, false)]
+    fn test_ensure_cadence_within_bounds(
+        #[case] period: CalendarPeriod,
+        #[case] missed_windows: u32,
+        #[case] max_inactivity_period: u64,
+        #[case] allowed: bool,
+    ) {
+        // one month is 28 days at the shortest
+        let config = Config {
+            min_inactivity_period: Duration::from_seconds(28 * 24 * 60 * 60).unwrap(),
+            max_inactivity_period: Duration::from_seconds(max_inactivity_period).unwrap(),
+            max_beneficiaries: 2,
+        };
+        let params = EOLParams {
+            cadence: Some(Cadence {
+                period,
+                missed_windows,
+            }),
+            ..EOLParams::default()
+        };
+
+        assert_eq!(config.ensure_within_bounds(&params).is_ok(), allowed);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(config().validate().is_ok());
+        assert!(Config {
+            min_inactivity_period: Duration::from_seconds(1_001).unwrap(),
+            ..config()
+        }
+        .validate()
+        .is_err());
+    }
+}
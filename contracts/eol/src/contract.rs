@@ -1,21 +1,33 @@
-use crate::admin::Admin;
-use crate::authenticator::{self};
+use crate::admin::{Admin, AdminOp, PendingAdminOp};
+use crate::authenticator::{self, AuthenticatorError, CompositeId, CompositeLimits};
+use crate::eol::{BeneficiaryKey, Duration};
+use crate::migrate::{migrate_state, MIGRATIONS};
 use crate::msg::{
-    AdminCandidateResponse, AdminResponse, EOLResponse, EOLsByAccountResponse,
-    ExecuteMsg, InstantiateMsg, QueryMsg,
-    SudoMsg,
+    AdminCandidateResponse, AdminResponse, AdminTimelockResponse, AssetTransfersResponse,
+    AuthenticatorTreeResponse, ConfigResponse, DenomRemovalTarget, EOLResponse,
+    EOLsByAccountResponse, EOLsByRootResponse, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    PauseStatusResponse, PendingAdminOpsResponse, PriceResolutionConfigResponse, QueryMsg, SudoMsg,
+    TrackedDenom, TrackedDenomsResponse,
+};
+use crate::pause::PauseScope;
+use crate::price::{PriceError, PriceResolutionConfig};
+use crate::state::{
+    eols_under, ADMIN, ADMIN_TIMELOCK, ASSET_TRANSFERS, CONFIG, EOLS, NEXT_ADMIN_OP_ID,
+    PAUSE_STATUS, PENDING_ADMIN_OPS, PRICE_INFOS, PRICE_RESOLUTION_CONFIG,
 };
-use crate::state::{ADMIN, EOLS};
 use crate::ContractError;
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response
-    , Storage, Timestamp,
+    ensure, to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response,
+    StdResult, Storage, Timestamp,
 };
+use cw_storage_plus::Bound;
 
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use osmosis_std::types::osmosis::smartaccount::v1beta1::SmartaccountQuerier;
+use std::str::FromStr;
 
 const CONTRACT_NAME: &str = "crates.io:eol";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -25,7 +37,7 @@ const MAX_LIMIT: u32 = 100;
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
@@ -37,13 +49,27 @@ pub fn instantiate(
         ADMIN.save(deps.storage, &Admin::new(admin))?;
     }
 
+    if let Some(price_resolution_config) = msg.price_resolution_config {
+        price_resolution_config.validate()?;
+        PRICE_RESOLUTION_CONFIG.save(deps.storage, &price_resolution_config)?;
+    }
+    if let Some(config) = msg.config {
+        config.validate()?;
+        CONFIG.save(deps.storage, &config)?;
+    }
+    if let Some(admin_timelock) = msg.admin_timelock {
+        ADMIN_TIMELOCK.save(deps.storage, &admin_timelock)?;
+    }
+
+    track_denoms(deps, &env, msg.tracked_denoms)?;
+
     Ok(Response::new().add_attribute("action", "instantiate"))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
@@ -53,7 +79,229 @@ pub fn execute(
         ExecuteMsg::RejectAdminTransfer {} => reject_admin_transfer(deps, info),
         ExecuteMsg::CancelAdminTransfer {} => cancel_admin_transfer(deps, info),
         ExecuteMsg::RevokeAdmin {} => revoke_admin(deps, info),
+        ExecuteMsg::QueueAdminOp { op } => queue_admin_op(deps, env, info, op),
+        ExecuteMsg::ExecuteAdminOp { id } => execute_admin_op(deps, env, info, id),
+        ExecuteMsg::CancelAdminOp { id } => cancel_admin_op(deps, info, id),
+        ExecuteMsg::SetPriceResolutionConfig {
+            price_resolution_config,
+        } => set_admin_op(
+            deps,
+            env,
+            info,
+            AdminOp::SetPriceResolutionConfig {
+                price_resolution_config,
+            },
+        ),
+        ExecuteMsg::SetTrackedDenoms { tracked_denoms } => set_admin_op(
+            deps,
+            env,
+            info,
+            AdminOp::SetTrackedDenoms { tracked_denoms },
+        ),
+        ExecuteMsg::RemoveTrackedDenoms { target } => {
+            set_admin_op(deps, env, info, AdminOp::RemoveTrackedDenoms { target })
+        }
+        ExecuteMsg::SetConfig { config } => {
+            set_admin_op(deps, env, info, AdminOp::SetConfig { config })
+        }
+        ExecuteMsg::Pause { scope } => set_admin_op(deps, env, info, AdminOp::Pause { scope }),
+        ExecuteMsg::Unpause { scope } => set_admin_op(deps, env, info, AdminOp::Unpause { scope }),
+        ExecuteMsg::RevealBeneficiary {
+            account,
+            authenticator_id,
+            address,
+            pubkey,
+            salt,
+        } => reveal_beneficiary(deps, env, account, authenticator_id, address, pubkey, salt),
+    }
+}
+
+fn reveal_beneficiary(
+    deps: DepsMut,
+    env: Env,
+    account: String,
+    authenticator_id: String,
+    address: String,
+    pubkey: BeneficiaryKey,
+    salt: Binary,
+) -> Result<Response, ContractError> {
+    let account = deps.api.addr_validate(&account)?;
+    let address = deps.api.addr_validate(&address)?;
+    let key = (&account, authenticator_id.as_str());
+
+    let mut eol = EOLS.load(deps.storage, key)?;
+    eol.ensure_out_of_limit(env.block.time)?;
+    eol.reveal_beneficiary(address.clone(), pubkey, salt.as_slice())?;
+    EOLS.save(deps.storage, key, &eol)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "reveal_beneficiary")
+        .add_attribute("beneficiary", address))
+}
+
+/// Apply `op` right away, only while no admin timelock is set unless it bypasses the timelock
+fn set_admin_op(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    op: AdminOp,
+) -> Result<Response, ContractError> {
+    authorize_admin(deps.storage, &info.sender)?;
+    let timelock = ADMIN_TIMELOCK.may_load(deps.storage)?.unwrap_or_default();
+    ensure!(
+        timelock == Duration::default() || op.bypasses_timelock(),
+        ContractError::Timelocked { timelock }
+    );
+
+    apply_admin_op(deps, &env, op)
+}
+
+fn queue_admin_op(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    op: AdminOp,
+) -> Result<Response, ContractError> {
+    authorize_admin(deps.storage, &info.sender)?;
+    op.validate()?;
+
+    let timelock = ADMIN_TIMELOCK.may_load(deps.storage)?.unwrap_or_default();
+    let pending = PendingAdminOp::new(op, env.block.time, timelock)?;
+    let id = NEXT_ADMIN_OP_ID.may_load(deps.storage)?.unwrap_or_default();
+    PENDING_ADMIN_OPS.save(deps.storage, id, &pending)?;
+    NEXT_ADMIN_OP_ID.save(deps.storage, &(id + 1))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "queue_admin_op")
+        .add_attribute("id", id.to_string())
+        .add_attribute("executable_at", pending.executable_at.to_string()))
+}
+
+fn execute_admin_op(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    authorize_admin(deps.storage, &info.sender)?;
+    let pending = PENDING_ADMIN_OPS
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::NotFound {})?;
+    pending.ensure_executable(env.block.time)?;
+    PENDING_ADMIN_OPS.remove(deps.storage, id);
+
+    Ok(apply_admin_op(deps, &env, pending.op)?.add_attribute("id", id.to_string()))
+}
+
+fn cancel_admin_op(deps: DepsMut, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+    authorize_admin(deps.storage, &info.sender)?;
+    ensure!(
+        PENDING_ADMIN_OPS.has(deps.storage, id),
+        ContractError::NotFound {}
+    );
+    PENDING_ADMIN_OPS.remove(deps.storage, id);
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_admin_op")
+        .add_attribute("id", id.to_string()))
+}
+
+fn apply_admin_op(deps: DepsMut, env: &Env, op: AdminOp) -> Result<Response, ContractError> {
+    op.validate()?;
+
+    match op {
+        AdminOp::SetConfig { config } => {
+            CONFIG.save(deps.storage, &config)?;
+
+            Ok(Response::new().add_attribute("action", "set_config"))
+        }
+        AdminOp::SetPriceResolutionConfig {
+            price_resolution_config,
+        } => set_price_resolution_config(deps.storage, price_resolution_config),
+        AdminOp::SetTrackedDenoms { tracked_denoms } => {
+            track_denoms(deps, env, tracked_denoms)?;
+
+            Ok(Response::new().add_attribute("action", "set_tracked_denoms"))
+        }
+        AdminOp::RemoveTrackedDenoms { target } => remove_tracked_denoms(deps.storage, target),
+        AdminOp::Pause { scope } => set_paused(deps.storage, scope, true),
+        AdminOp::Unpause { scope } => set_paused(deps.storage, scope, false),
+        AdminOp::SetTimelock { delay } => {
+            ADMIN_TIMELOCK.save(deps.storage, &delay)?;
+
+            Ok(Response::new()
+                .add_attribute("action", "set_admin_timelock")
+                .add_attribute("delay", delay.to_string()))
+        }
+    }
+}
+
+fn set_paused(
+    storage: &mut dyn Storage,
+    scope: PauseScope,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let mut status = PAUSE_STATUS.may_load(storage)?.unwrap_or_default();
+    status.set(scope, paused);
+    PAUSE_STATUS.save(storage, &status)?;
+
+    Ok(Response::new()
+        .add_attribute("action", if paused { "pause" } else { "unpause" })
+        .add_attribute("scope", scope.to_string()))
+}
+
+fn set_price_resolution_config(
+    storage: &mut dyn Storage,
+    price_resolution_config: PriceResolutionConfig,
+) -> Result<Response, ContractError> {
+    // swap routes of the tracked denoms all end in the previous quote denom
+    let quote_denom_changed = PRICE_RESOLUTION_CONFIG
+        .may_load(storage)?
+        .is_some_and(|config| config.quote_denom != price_resolution_config.quote_denom);
+    if quote_denom_changed {
+        PRICE_INFOS.clear(storage);
+    }
+
+    PRICE_RESOLUTION_CONFIG.save(storage, &price_resolution_config)?;
+
+    Ok(Response::new().add_attribute("action", "set_price_resolution_config"))
+}
+
+fn track_denoms(
+    deps: DepsMut,
+    env: &Env,
+    tracked_denoms: Vec<TrackedDenom>,
+) -> Result<(), ContractError> {
+    if tracked_denoms.is_empty() {
+        return Ok(());
+    }
+
+    let config = PRICE_RESOLUTION_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(PriceError::NotConfigured {})?;
+
+    for TrackedDenom { denom, swap_routes } in tracked_denoms {
+        let price_info = config.track_denom(&deps.querier, &denom, swap_routes, env.block.time)?;
+        PRICE_INFOS.save(deps.storage, &denom, &price_info)?;
+    }
+
+    Ok(())
+}
+
+fn remove_tracked_denoms(
+    storage: &mut dyn Storage,
+    target: DenomRemovalTarget,
+) -> Result<Response, ContractError> {
+    match target {
+        DenomRemovalTarget::All => PRICE_INFOS.clear(storage),
+        DenomRemovalTarget::Partial(denoms) => {
+            for denom in denoms {
+                PRICE_INFOS.remove(storage, &denom);
+            }
+        }
     }
+
+    Ok(Response::new().add_attribute("action", "remove_tracked_denoms"))
 }
 
 fn transfer_admin(
@@ -74,6 +322,8 @@ fn claim_admin_transfer(deps: DepsMut, info: MessageInfo) -> Result<Response, Co
     update_admin(deps.storage, |admin| {
         admin.authorized_claim_admin_transfer(&info.sender)
     })?;
+    // operations queued under the previous admin don't carry over
+    PENDING_ADMIN_OPS.clear(deps.storage);
 
     Ok(Response::new().add_attribute("action", "claim_admin"))
 }
@@ -118,6 +368,24 @@ fn authorize_admin(store: &mut dyn Storage, sender: &Addr) -> Result<(), Contrac
     admin.authorize_admin(sender)
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let from = get_contract_version(deps.storage)?.version;
+    let applied = migrate_state(
+        deps.storage,
+        &env,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        MIGRATIONS,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", from)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("transforms", applied.join(",")))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
     match msg {
@@ -149,17 +417,18 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractErro
             authenticator_id,
         } => {
             let account = deps.api.addr_validate(&account)?;
-            to_json_binary(&query_eol(
-                deps,
-                account,
-                authenticator_id,
-                env.block.time,
-            )?)
+            to_json_binary(&query_eol(deps, account, authenticator_id, env.block.time)?)
         }
         QueryMsg::EOLs { account } => {
             let account = deps.api.addr_validate(&account)?;
             to_json_binary(&query_spendings_by_account(deps, account)?)
         }
+        QueryMsg::EOLsByRoot { account, root } => {
+            let account = deps.api.addr_validate(&account)?;
+            to_json_binary(&EOLsByRootResponse {
+                eols: eols_under(deps.storage, &account, &CompositeId::new(root, vec![]))?,
+            })
+        }
         QueryMsg::Admin {} => to_json_binary(&AdminResponse {
             admin: ADMIN
                 .may_load(deps.storage)?
@@ -172,8 +441,84 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractErro
                 .and_then(|a| a.candidate_once())
                 .map(|a| a.to_string()),
         }),
+        QueryMsg::AdminTimelock {} => to_json_binary(&AdminTimelockResponse {
+            timelock: ADMIN_TIMELOCK.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::PendingAdminOps { start_after, limit } => {
+            let limit = limit.unwrap_or(MAX_LIMIT).min(MAX_LIMIT) as usize;
+            let ops = PENDING_ADMIN_OPS
+                .range(
+                    deps.storage,
+                    start_after.map(Bound::exclusive),
+                    None,
+                    Order::Ascending,
+                )
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            to_json_binary(&PendingAdminOpsResponse { ops })
+        }
+        QueryMsg::Config {} => to_json_binary(&ConfigResponse {
+            config: CONFIG.may_load(deps.storage)?,
+        }),
+        QueryMsg::PauseStatus {} => to_json_binary(&PauseStatusResponse {
+            status: PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::PriceResolutionConfig {} => to_json_binary(&PriceResolutionConfigResponse {
+            price_resolution_config: PRICE_RESOLUTION_CONFIG.may_load(deps.storage)?,
+        }),
+        QueryMsg::TrackedDenoms {} => to_json_binary(&TrackedDenomsResponse {
+            tracked_denoms: PRICE_INFOS
+                .range(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()?,
+        }),
+        QueryMsg::AssetTransfers {
+            account,
+            authenticator_id,
+        } => {
+            let account = deps.api.addr_validate(&account)?;
+            to_json_binary(&AssetTransfersResponse {
+                asset_transfers: ASSET_TRANSFERS
+                    .prefix((&account, authenticator_id.as_str()))
+                    .range(deps.storage, None, None, Order::Ascending)
+                    .collect::<StdResult<Vec<_>>>()?,
+            })
+        }
+        QueryMsg::AuthenticatorTree {
+            account,
+            authenticator_id,
+        } => {
+            let account = deps.api.addr_validate(&account)?;
+            to_json_binary(&query_authenticator_tree(
+                deps,
+                &env,
+                account,
+                authenticator_id,
+            )?)
+        }
     }
-        .map_err(ContractError::from)
+    .map_err(ContractError::from)
+}
+
+pub fn query_authenticator_tree(
+    deps: Deps,
+    env: &Env,
+    account: Addr,
+    authenticator_id: String,
+) -> Result<AuthenticatorTreeResponse, ContractError> {
+    let CompositeId { root, path } =
+        CompositeId::from_str(&authenticator_id).map_err(AuthenticatorError::from)?;
+
+    let authenticator = SmartaccountQuerier::new(&deps.querier)
+        .get_authenticator(account.to_string(), root)?
+        .account_authenticator
+        .ok_or(ContractError::NotFound {})?;
+
+    let limits = CompositeLimits::default();
+    let tree = authenticator::authenticator_tree(authenticator, &env.contract.address, &limits)?
+        .descendant(&path)
+        .ok_or(ContractError::NotFound {})?;
+
+    Ok(AuthenticatorTreeResponse { tree })
 }
 
 pub fn query_eol(
@@ -183,9 +528,7 @@ pub fn query_eol(
     at: Timestamp,
 ) -> Result<EOLResponse, ContractError> {
     match EOLS.may_load(deps.storage, (&account, authenticator_id.as_str()))? {
-        Some(eol) => Ok(EOLResponse {
-            eol,
-        }),
+        Some(eol) => Ok(EOLResponse { eol }),
         None => Err(ContractError::NotFound {}.into()),
     }
 }
@@ -206,6 +549,289 @@ pub fn query_spendings_by_account(
     Ok(EOLsByAccountResponse { eols })
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::msg::{ConfigResponse, PauseStatusResponse};
+    use crate::pause::PauseStatus;
+    use crate::price::PriceInfo;
+    use cosmwasm_std::from_json;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::Decimal;
+
+    fn instantiate_msg(admin_timelock: Option<Duration>) -> InstantiateMsg {
+        InstantiateMsg {
+            admin: Some("admin".to_string()),
+            price_resolution_config: None,
+            tracked_denoms: vec![],
+            config: None,
+            admin_timelock,
+        }
+    }
+
+    #[test]
+    fn test_timelocked_admin_ops() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let admin = mock_info("admin", &[]);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            instantiate_msg(Some(Duration::from_seconds(100).unwrap())),
+        )
+        .unwrap();
+
+        // direct changes are refused while timelocked
+        let remove = AdminOp::RemoveTrackedDenoms {
+            target: DenomRemovalTarget::All,
+        };
+        assert_eq!(
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                admin.clone(),
+                ExecuteMsg::RemoveTrackedDenoms {
+                    target: DenomRemovalTarget::All
+                }
+            )
+            .unwrap_err(),
+            ContractError::Timelocked {
+                timelock: Duration::from_seconds(100).unwrap()
+            }
+        );
+
+        // only the admin queues
+        let queue = ExecuteMsg::QueueAdminOp { op: remove.clone() };
+        assert_eq!(
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("someone", &[]),
+                queue.clone()
+            )
+            .unwrap_err(),
+            ContractError::Unauthorized {}
+        );
+        execute(deps.as_mut(), env.clone(), admin.clone(), queue.clone()).unwrap();
+        execute(deps.as_mut(), env.clone(), admin.clone(), queue).unwrap();
+
+        let res: PendingAdminOpsResponse = from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::PendingAdminOps {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let pending =
+            PendingAdminOp::new(remove, env.block.time, Duration::from_seconds(100).unwrap())
+                .unwrap();
+        assert_eq!(res.ops, vec![(0, pending.clone()), (1, pending)]);
+
+        // not before the timelock went by
+        assert_eq!(
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                admin.clone(),
+                ExecuteMsg::ExecuteAdminOp { id: 0 }
+            )
+            .unwrap_err(),
+            ContractError::AdminOpNotReady {
+                executable_at: env.block.time.plus_seconds(100)
+            }
+        );
+
+        PRICE_INFOS
+            .save(
+                deps.as_mut().storage,
+                "uosmo",
+                &PriceInfo {
+                    price: Decimal::one(),
+                    last_updated_time: env.block.time,
+                    swap_routes: vec![],
+                },
+            )
+            .unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            ExecuteMsg::ExecuteAdminOp { id: 0 },
+        )
+        .unwrap();
+        assert!(PRICE_INFOS.is_empty(deps.as_ref().storage));
+
+        // cancelled operations can't be executed
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            ExecuteMsg::CancelAdminOp { id: 1 },
+        )
+        .unwrap();
+        assert_eq!(
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                admin,
+                ExecuteMsg::ExecuteAdminOp { id: 1 }
+            )
+            .unwrap_err(),
+            ContractError::NotFound {}
+        );
+    }
+
+    #[test]
+    fn test_pause_bypasses_timelock() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let admin = mock_info("admin", &[]);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            instantiate_msg(Some(Duration::from_seconds(100).unwrap())),
+        )
+        .unwrap();
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            ExecuteMsg::Pause {
+                scope: PauseScope::Beneficiaries,
+            },
+        )
+        .unwrap();
+        let res: PauseStatusResponse =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::PauseStatus {}).unwrap())
+                .unwrap();
+        assert_eq!(
+            res.status,
+            PauseStatus {
+                beneficiaries: true,
+                registrations: false,
+            }
+        );
+
+        // lifting a pause or the timelock itself still waits for it
+        let unpause = ExecuteMsg::Unpause {
+            scope: PauseScope::Beneficiaries,
+        };
+        let set_timelock = ExecuteMsg::QueueAdminOp {
+            op: AdminOp::SetTimelock {
+                delay: Duration::from_seconds(0).unwrap(),
+            },
+        };
+        assert_eq!(
+            execute(deps.as_mut(), env.clone(), admin.clone(), unpause).unwrap_err(),
+            ContractError::Timelocked {
+                timelock: Duration::from_seconds(100).unwrap()
+            }
+        );
+        execute(deps.as_mut(), env.clone(), admin.clone(), set_timelock).unwrap();
+        assert_eq!(
+            execute(
+                deps.as_mut(),
+                env,
+                admin,
+                ExecuteMsg::ExecuteAdminOp { id: 0 }
+            )
+            .unwrap_err(),
+            ContractError::AdminOpNotReady {
+                executable_at: mock_env().block.time.plus_seconds(100)
+            }
+        );
+    }
+
+    #[test]
+    fn test_admin_transfer_drops_pending_admin_ops() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let admin = mock_info("admin", &[]);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            instantiate_msg(Some(Duration::from_seconds(100).unwrap())),
+        )
+        .unwrap();
+
+        let queue = ExecuteMsg::QueueAdminOp {
+            op: AdminOp::SetTimelock {
+                delay: Duration::from_seconds(0).unwrap(),
+            },
+        };
+        execute(deps.as_mut(), env.clone(), admin.clone(), queue).unwrap();
+        let transfer = ExecuteMsg::TransferAdmin {
+            address: "candidate".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), admin, transfer).unwrap();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("candidate", &[]),
+            ExecuteMsg::ClaimAdminTransfer {},
+        )
+        .unwrap();
+
+        let res: PendingAdminOpsResponse = from_json(
+            query(
+                deps.as_ref(),
+                env,
+                QueryMsg::PendingAdminOps {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(res.ops.is_empty());
+    }
+
+    #[test]
+    fn test_admin_ops_apply_directly_without_timelock() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let admin = mock_info("admin", &[]);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            instantiate_msg(None),
+        )
+        .unwrap();
+
+        let config = Config {
+            min_inactivity_period: Duration::from_seconds(1).unwrap(),
+            max_inactivity_period: Duration::from_seconds(2).unwrap(),
+            max_beneficiaries: 1,
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin,
+            ExecuteMsg::SetConfig {
+                config: config.clone(),
+            },
+        )
+        .unwrap();
+
+        let res: ConfigResponse =
+            from_json(query(deps.as_ref(), env, QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(res.config, Some(config));
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use std::collections::BTreeMap;
@@ -0,0 +1,489 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ensure, from_json, Binary, StdError, Uint128};
+use cw_authenticator::Any;
+use osmosis_std::types::cosmwasm::wasm::v1::MsgExecuteContract;
+
+use super::error::{EOLError, EOLResult};
+use super::params::EOLParams;
+use super::wasm::entry_point;
+
+/// Entry points of the cw20 standard that move tokens out of the account
+#[cw_serde]
+enum Cw20ExecuteMsg {
+    Transfer {
+        recipient: String,
+        amount: Uint128,
+    },
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    Burn {
+        amount: Uint128,
+    },
+}
+
+/// Entry points of the cw721 standard that move tokens out of the account
+#[cw_serde]
+enum Cw721ExecuteMsg {
+    TransferNft {
+        recipient: String,
+        token_id: String,
+    },
+    SendNft {
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    },
+    Burn {
+        token_id: String,
+    },
+}
+
+/// Entry points of the cw20 and cw721 standards that hand tokens to, or take them from,
+/// other addresses in ways the rules can't account for
+const UNSUPPORTED_ENTRY_POINTS: &[&str] = &[
+    "increase_allowance",
+    "decrease_allowance",
+    "transfer_from",
+    "send_from",
+    "burn_from",
+    "approve",
+    "revoke",
+    "approve_all",
+    "revoke_all",
+];
+
+#[cw_serde]
+pub enum AssetKind {
+    Cw20,
+    Cw721,
+}
+
+/// Tokens of a cw20 or cw721 `contract` moved by a single execution
+#[derive(Debug, PartialEq)]
+pub struct AssetTransfer {
+    pub kind: AssetKind,
+    pub contract: String,
+    /// Where the tokens went, unset when they were burned
+    pub recipient: Option<String>,
+    /// Amount of cw20 tokens, or 1 for an NFT
+    pub amount: Uint128,
+}
+
+/// Contract execution as seen by the cw20 and cw721 rules
+#[derive(Debug, PartialEq)]
+pub enum AssetMsg {
+    /// Transfer, send or burn of the account's tokens
+    Transfer(AssetTransfer),
+    /// Allowance or approval, or a transfer, send or burn that doesn't decode
+    Unsupported {
+        contract: String,
+        entry_point: String,
+    },
+    /// Any other entry point
+    Other { contract: String },
+}
+
+impl AssetMsg {
+    /// Decode `msg` if it executes a contract
+    pub fn decode(msg: &Any) -> EOLResult<Option<Self>> {
+        if msg.type_url != MsgExecuteContract::TYPE_URL {
+            return Ok(None);
+        }
+
+        let execute = MsgExecuteContract::try_from(msg.value.clone())
+            .map_err(|_| EOLError::undecodable_message(&msg.type_url))?;
+        let contract = execute.contract;
+
+        let Some(entry_point) = entry_point(&execute.msg) else {
+            return Ok(Some(AssetMsg::Other { contract }));
+        };
+
+        let transfer = |kind, recipient, amount| {
+            Some(AssetMsg::Transfer(AssetTransfer {
+                kind,
+                contract: contract.clone(),
+                recipient,
+                amount,
+            }))
+        };
+
+        let decoded = match entry_point.as_str() {
+            "transfer" | "send" | "burn" | "transfer_nft" | "send_nft" => {
+                if let Ok(cw20) = from_json(&execute.msg) {
+                    match cw20 {
+                        Cw20ExecuteMsg::Transfer { recipient, amount } => {
+                            transfer(AssetKind::Cw20, Some(recipient), amount)
+                        }
+                        Cw20ExecuteMsg::Send {
+                            contract, amount, ..
+                        } => transfer(AssetKind::Cw20, Some(contract), amount),
+                        Cw20ExecuteMsg::Burn { amount } => transfer(AssetKind::Cw20, None, amount),
+                    }
+                } else if let Ok(cw721) = from_json(&execute.msg) {
+                    match cw721 {
+                        Cw721ExecuteMsg::TransferNft { recipient, .. } => {
+                            transfer(AssetKind::Cw721, Some(recipient), Uint128::one())
+                        }
+                        Cw721ExecuteMsg::SendNft { contract, .. } => {
+                            transfer(AssetKind::Cw721, Some(contract), Uint128::one())
+                        }
+                        Cw721ExecuteMsg::Burn { .. } => {
+                            transfer(AssetKind::Cw721, None, Uint128::one())
+                        }
+                    }
+                } else {
+                    None
+                }
+            }
+            entry_point if !UNSUPPORTED_ENTRY_POINTS.contains(&entry_point) => {
+                return Ok(Some(AssetMsg::Other { contract }));
+            }
+            _ => None,
+        };
+
+        // anything resembling a token movement that can't be accounted for fails closed
+        Ok(decoded.or(Some(AssetMsg::Unsupported {
+            contract,
+            entry_point,
+        })))
+    }
+}
+
+/// cw20 or cw721 contract whose tokens beneficiaries may move
+#[cw_serde]
+pub struct AssetRule {
+    pub contract: String,
+
+    /// Most each beneficiary may move in total, in tokens for cw20 and in NFTs for cw721.
+    /// Uncapped when unset.
+    #[serde(default)]
+    pub cap: Option<Uint128>,
+}
+
+/// Amount of a contract's tokens a beneficiary moved out of the account
+#[cw_serde]
+pub struct AssetTotal {
+    pub contract: String,
+    pub amount: Uint128,
+}
+
+/// Running totals of the cw20 and cw721 tokens a beneficiary moved out of the account
+#[cw_serde]
+#[derive(Default)]
+pub struct AssetTransfers {
+    pub totals: Vec<AssetTotal>,
+}
+
+impl AssetTransfers {
+    /// Add `transfer` to the totals, holding it to the cap of its `rule` if any
+    pub fn record(
+        &mut self,
+        rule: Option<&AssetRule>,
+        transfer: &AssetTransfer,
+    ) -> EOLResult<&mut Self> {
+        let index = match self
+            .totals
+            .iter()
+            .position(|total| total.contract == transfer.contract)
+        {
+            Some(index) => index,
+            None => {
+                self.totals.push(AssetTotal {
+                    contract: transfer.contract.clone(),
+                    amount: Uint128::zero(),
+                });
+                self.totals.len() - 1
+            }
+        };
+
+        let total = &mut self.totals[index];
+        total.amount = total
+            .amount
+            .checked_add(transfer.amount)
+            .map_err(StdError::from)?;
+
+        if let Some(cap) = rule.and_then(|rule| rule.cap) {
+            ensure!(
+                total.amount <= cap,
+                EOLError::AssetCapExceeded {
+                    contract: transfer.contract.clone(),
+                    cap,
+                }
+            );
+        }
+
+        Ok(self)
+    }
+}
+
+impl EOLParams {
+    /// Whether transfers of cw20 or cw721 tokens are restricted
+    pub fn restricts_assets(&self) -> bool {
+        self.cw20_rules.is_some() || self.cw721_rules.is_some()
+    }
+
+    /// Whether `contract` is listed by the cw20 or cw721 rules
+    fn is_asset_contract(&self, contract: &str) -> bool {
+        self.cw20_rules
+            .iter()
+            .chain(&self.cw721_rules)
+            .flatten()
+            .any(|rule| rule.contract == contract)
+    }
+
+    /// Ensure `transfer` is of an allowed contract, returning the rule it falls under.
+    /// Contracts of a kind without rules are allowed uncapped.
+    pub fn ensure_asset_allowed(&self, transfer: &AssetTransfer) -> EOLResult<Option<&AssetRule>> {
+        let rules = match transfer.kind {
+            AssetKind::Cw20 => &self.cw20_rules,
+            AssetKind::Cw721 => &self.cw721_rules,
+        };
+
+        let Some(rules) = rules else {
+            return Ok(None);
+        };
+
+        rules
+            .iter()
+            .find(|rule| rule.contract == transfer.contract)
+            .map(Some)
+            .ok_or_else(|| EOLError::asset_not_allowed(&transfer.contract))
+    }
+
+    /// Ensure `msg` is allowed by the cw20 and cw721 rules, returning the rule a transfer
+    /// falls under. Once any rules are set, allowances, approvals and transfers that don't
+    /// decode are rejected, and the contracts listed may only be sent transfers.
+    pub fn ensure_asset_msg_allowed(&self, msg: &AssetMsg) -> EOLResult<Option<&AssetRule>> {
+        match msg {
+            AssetMsg::Transfer(transfer) => self.ensure_asset_allowed(transfer),
+            AssetMsg::Unsupported { contract, .. } => {
+                ensure!(
+                    !self.restricts_assets(),
+                    EOLError::asset_msg_not_allowed(contract)
+                );
+                Ok(None)
+            }
+            AssetMsg::Other { contract } => {
+                ensure!(
+                    !self.is_asset_contract(contract),
+                    EOLError::asset_msg_not_allowed(contract)
+                );
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn execute(contract: &str, msg: &str) -> Any {
+        Any {
+            type_url: MsgExecuteContract::TYPE_URL.to_string(),
+            value: MsgExecuteContract {
+                sender: "account".to_string(),
+                contract: contract.to_string(),
+                msg: msg.as_bytes().to_vec(),
+                funds: vec![],
+            }
+            .into(),
+        }
+    }
+
+    fn transfer(kind: AssetKind, contract: &str, amount: u128) -> AssetTransfer {
+        AssetTransfer {
+            kind,
+            contract: contract.to_string(),
+            recipient: Some("heir".to_string()),
+            amount: Uint128::new(amount),
+        }
+    }
+
+    fn unsupported(contract: &str, entry_point: &str) -> AssetMsg {
+        AssetMsg::Unsupported {
+            contract: contract.to_string(),
+            entry_point: entry_point.to_string(),
+        }
+    }
+
+    fn other(contract: &str) -> AssetMsg {
+        AssetMsg::Other {
+            contract: contract.to_string(),
+        }
+    }
+
+    #[rstest]
+    #[case::cw20_transfer(
+        r#"{"transfer":{"recipient":"heir","amount":"100"}}"#,
+        AssetMsg::Transfer(transfer(AssetKind::Cw20, "token", 100))
+    )]
+    #[case::cw20_send(
+        r#"{"send":{"contract":"heir","amount":"100","msg":""}}"#,
+        AssetMsg::Transfer(transfer(AssetKind::Cw20, "token", 100))
+    )]
+    #[case::cw20_burn(
+        r#"{"burn":{"amount":"100"}}"#,
+        AssetMsg::Transfer(AssetTransfer {
+            recipient: None,
+            ..transfer(AssetKind::Cw20, "token", 100)
+        })
+    )]
+    #[case::cw721_transfer(
+        r#"{"transfer_nft":{"recipient":"heir","token_id":"1"}}"#,
+        AssetMsg::Transfer(transfer(AssetKind::Cw721, "token", 1))
+    )]
+    #[case::cw721_send(
+        r#"{"send_nft":{"contract":"heir","token_id":"1","msg":""}}"#,
+        AssetMsg::Transfer(transfer(AssetKind::Cw721, "token", 1))
+    )]
+    #[case::cw721_burn(
+        r#"{"burn":{"token_id":"1"}}"#,
+        AssetMsg::Transfer(AssetTransfer {
+            recipient: None,
+            ..transfer(AssetKind::Cw721, "token", 1)
+        })
+    )]
+    #[case::increase_allowance(
+        r#"{"increase_allowance":{"spender":"heir","amount":"100"}}"#,
+        unsupported("token", "increase_allowance")
+    )]
+    #[case::transfer_from(
+        r#"{"transfer_from":{"owner":"account","recipient":"heir","amount":"100"}}"#,
+        unsupported("token", "transfer_from")
+    )]
+    #[case::approve(
+        r#"{"approve":{"spender":"heir","token_id":"1"}}"#,
+        unsupported("token", "approve")
+    )]
+    #[case::approve_all(
+        r#"{"approve_all":{"operator":"heir"}}"#,
+        unsupported("token", "approve_all")
+    )]
+    #[case::transfer_unknown_field(
+        r#"{"transfer":{"recipient":"heir","amount":"100","memo":""}}"#,
+        unsupported("token", "transfer")
+    )]
+    #[case::other(r#"{"swap":{}}"#, other("token"))]
+    #[case::not_an_entry_point(r#"{"swap":{},"transfer":{}}"#, other("token"))]
+    fn test_decode(#[case] msg: &str, #[case] expected: AssetMsg) {
+        assert_eq!(
+            AssetMsg::decode(&execute("token", msg)).unwrap(),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn test_decode_other_messages() {
+        let msg = Any {
+            type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+            value: Binary::default(),
+        };
+        assert_eq!(AssetMsg::decode(&msg).unwrap(), None);
+    }
+
+    #[test]
+    fn test_ensure_asset_allowed() {
+        let rule = AssetRule {
+            contract: "token".to_string(),
+            cap: None,
+        };
+        let params = EOLParams {
+            cw20_rules: Some(vec![rule.clone()]),
+            ..EOLParams::default()
+        };
+
+        assert_eq!(
+            params.ensure_asset_allowed(&transfer(AssetKind::Cw20, "token", 1)),
+            Ok(Some(&rule))
+        );
+        assert_eq!(
+            params.ensure_asset_allowed(&transfer(AssetKind::Cw20, "other", 1)),
+            Err(EOLError::asset_not_allowed("other"))
+        );
+        // no cw721 rules
+        assert_eq!(
+            params.ensure_asset_allowed(&transfer(AssetKind::Cw721, "nft", 1)),
+            Ok(None)
+        );
+    }
+
+    #[rstest]
+    #[case::no_rules(None, unsupported("token", "approve"), Ok(()))]
+    #[case::unsupported(
+        Some("token"),
+        unsupported("other", "approve"),
+        Err(EOLError::asset_msg_not_allowed("other"))
+    )]
+    #[case::listed_other(
+        Some("token"),
+        other("token"),
+        Err(EOLError::asset_msg_not_allowed("token"))
+    )]
+    #[case::unlisted_other(Some("token"), other("pool"), Ok(()))]
+    #[case::no_rules_other(None, other("token"), Ok(()))]
+    fn test_ensure_asset_msg_allowed(
+        #[case] cw721_contract: Option<&str>,
+        #[case] msg: AssetMsg,
+        #[case] expected: EOLResult<()>,
+    ) {
+        let params = EOLParams {
+            cw721_rules: cw721_contract.map(|contract| {
+                vec![AssetRule {
+                    contract: contract.to_string(),
+                    cap: None,
+                }]
+            }),
+            ..EOLParams::default()
+        };
+
+        assert_eq!(params.ensure_asset_msg_allowed(&msg).map(|_| ()), expected);
+    }
+
+    #[test]
+    fn test_record() {
+        let rule = AssetRule {
+            contract: "token".to_string(),
+            cap: Some(Uint128::new(100)),
+        };
+        let mut transfers = AssetTransfers::default();
+
+        transfers
+            .record(Some(&rule), &transfer(AssetKind::Cw20, "token", 60))
+            .unwrap()
+            .record(None, &transfer(AssetKind::Cw721, "nft", 1))
+            .unwrap();
+        assert_eq!(
+            transfers.totals,
+            vec![
+                AssetTotal {
+                    contract: "token".to_string(),
+                    amount: Uint128::new(60),
+                },
+                AssetTotal {
+                    contract: "nft".to_string(),
+                    amount: Uint128::new(1),
+                },
+            ]
+        );
+
+        assert_eq!(
+            transfers
+                .clone()
+                .record(Some(&rule), &transfer(AssetKind::Cw20, "token", 41))
+                .unwrap_err(),
+            EOLError::AssetCapExceeded {
+                contract: "token".to_string(),
+                cap: Uint128::new(100),
+            }
+        );
+        transfers
+            .record(Some(&rule), &transfer(AssetKind::Cw20, "token", 40))
+            .unwrap();
+        assert_eq!(transfers.totals[0].amount, Uint128::new(100));
+    }
+}
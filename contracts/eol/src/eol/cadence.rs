@@ -0,0 +1,243 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ensure, StdError, StdResult, Timestamp};
+use time::{Date, Month, OffsetDateTime};
+
+use super::duration::Duration;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Most windows in a row a cadence may allow to be missed, a century of yearly windows
+pub const MAX_MISSED_WINDOWS: u32 = 100;
+
+/// Calendar period the owner must check in within, in UTC
+#[cw_serde]
+#[derive(Copy)]
+pub enum CalendarPeriod {
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl CalendarPeriod {
+    fn months(&self) -> i64 {
+        match self {
+            CalendarPeriod::Monthly => 1,
+            CalendarPeriod::Quarterly => 3,
+            CalendarPeriod::Yearly => 12,
+        }
+    }
+
+    /// Shortest and longest window in days
+    fn days(&self) -> (u64, u64) {
+        match self {
+            CalendarPeriod::Monthly => (28, 31),
+            CalendarPeriod::Quarterly => (90, 92),
+            CalendarPeriod::Yearly => (365, 366),
+        }
+    }
+}
+
+/// Check-in cadence aligned on calendar windows rather than a rolling inactivity period.
+/// The owner must show activity within every window, and the account goes dormant
+/// once `missed_windows` windows in a row went by without any.
+#[cw_serde]
+pub struct Cadence {
+    pub period: CalendarPeriod,
+    pub missed_windows: u32,
+}
+
+impl Cadence {
+    pub fn validate(&self) -> StdResult<()> {
+        ensure!(
+            self.missed_windows > 0,
+            StdError::generic_err("cadence must allow at least one missed window")
+        );
+        ensure!(
+            self.missed_windows <= MAX_MISSED_WINDOWS,
+            StdError::generic_err(format!(
+                "cadence may allow at most {MAX_MISSED_WINDOWS} missed windows"
+            ))
+        );
+
+        Ok(())
+    }
+
+    /// Time the account goes dormant at when the owner was last active at `last_active`,
+    /// the start of the window following the last one missed
+    pub fn dormant_at(&self, last_active: Timestamp) -> StdResult<Timestamp> {
+        let active = OffsetDateTime::from_unix_timestamp(last_active.seconds() as i64)
+            .map_err(|e| StdError::generic_err(e.to_string()))?;
+        let months = self.period.months();
+        let active_month = active.year() as i64 * 12 + (u8::from(active.month()) as i64 - 1);
+        let active_window = active_month.div_euclid(months);
+
+        window_start((active_window + self.missed_windows as i64 + 1) * months)
+    }
+
+    /// Shortest and longest the account may take to go dormant after the owner was last active
+    pub fn bounds(&self) -> StdResult<(Duration, Duration)> {
+        let (shortest, longest) = self.period.days();
+        let missed_windows = self.missed_windows as u64;
+
+        let span = |windows: u64, days: u64| {
+            windows
+                .checked_mul(days * SECONDS_PER_DAY)
+                .ok_or_else(|| StdError::generic_err("cadence span overflows"))
+                .and_then(Duration::from_seconds)
+        };
+
+        Ok((
+            span(missed_windows, shortest)?,
+            span(missed_windows + 1, longest)?,
+        ))
+    }
+}
+
+/// Midnight UTC on the first day of the month counted from year 0
+fn window_start(month: i64) -> StdResult<Timestamp> {
+    let out_of_range = || StdError::generic_err("cadence window out of range");
+
+    let year = i32::try_from(month.div_euclid(12)).map_err(|_| out_of_range())?;
+    let month = Month::try_from(month.rem_euclid(12) as u8 + 1).map_err(|_| out_of_range())?;
+    let start = Date::from_calendar_date(year, month, 1)
+        .map_err(|_| out_of_range())?
+        .midnight()
+        .assume_utc()
+        .unix_timestamp();
+
+    u64::try_from(start)
+        .map(Timestamp::from_seconds)
+        .map_err(|_| out_of_range())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use time::macros::datetime;
+
+    fn timestamp(datetime: OffsetDateTime) -> Timestamp {
+        Timestamp::from_seconds(datetime.unix_timestamp() as u64)
+    }
+
+    #[rstest]
+    #[case::monthly(
+        CalendarPeriod::Monthly,
+        1,
+        datetime!(2024-02-15 10:00 UTC),
+        datetime!(2024-04-01 0:00 UTC)
+    )]
+    #[case::monthly_end_of_year(
+        CalendarPeriod::Monthly,
+        2,
+        datetime!(2024-11-30 23:59 UTC),
+        datetime!(2025-02-01 0:00 UTC)
+    )]
+    #[case::quarterly(
+        CalendarPeriod::Quarterly,
+        2,
+        datetime!(2024-03-31 23:59 UTC),
+        datetime!(2024-10-01 0:00 UTC)
+    )]
+    #[case::quarterly_first_day(
+        CalendarPeriod::Quarterly,
+        1,
+        datetime!(2024-04-01 0:00 UTC),
+        datetime!(2024-10-01 0:00 UTC)
+    )]
+    #[case::yearly(
+        CalendarPeriod::Yearly,
+        1,
+        datetime!(2024-06-01 0:00 UTC),
+        datetime!(2026-01-01 0:00 UTC)
+    )]
+    fn test_dormant_at(
+        #[case] period: CalendarPeriod,
+        #[case] missed_windows: u32,
+        #[case] last_active: OffsetDateTime,
+        #[case] expected: OffsetDateTime,
+    ) {
+        let cadence = Cadence {
+            period,
+            missed_windows,
+        };
+
+        assert_eq!(
+            cadence.dormant_at(timestamp(last_active)).unwrap(),
+            timestamp(expected)
+        );
+    }
+
+    #[test]
+    fn test_dormant_at_out_of_range() {
+        let cadence = Cadence {
+            period: CalendarPeriod::Yearly,
+            missed_windows: u32::MAX,
+        };
+
+        assert!(cadence.dormant_at(Timestamp::from_seconds(0)).is_err());
+    }
+
+    #[rstest]
+    #[case(CalendarPeriod::Monthly)]
+    #[case(CalendarPeriod::Quarterly)]
+    #[case(CalendarPeriod::Yearly)]
+    fn test_bounds(#[case] period: CalendarPeriod) {
+        let cadence = Cadence {
+            period,
+            missed_windows: 3,
+        };
+        let (shortest, longest) = cadence.bounds().unwrap();
+
+        // active at the very end and the very start of a window
+        for last_active in [
+            datetime!(2023-12-31 23:59:59 UTC),
+            datetime!(2024-01-01 0:00 UTC),
+            datetime!(2024-07-01 0:00 UTC),
+        ] {
+            let last_active = timestamp(last_active);
+            let dormant_at = cadence.dormant_at(last_active).unwrap();
+            let span = dormant_at.nanos() - last_active.nanos();
+
+            assert!(shortest.nanos() <= span && span <= longest.nanos());
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Cadence {
+            period: CalendarPeriod::Monthly,
+            missed_windows: 0,
+        }
+        .validate()
+        .is_err());
+        assert!(Cadence {
+            period: CalendarPeriod::Monthly,
+            missed_windows: 1,
+        }
+        .validate()
+        .is_ok());
+        assert!(Cadence {
+            period: CalendarPeriod::Yearly,
+            missed_windows: MAX_MISSED_WINDOWS,
+        }
+        .validate()
+        .is_ok());
+        assert!(Cadence {
+            period: CalendarPeriod::Monthly,
+            missed_windows: MAX_MISSED_WINDOWS + 1,
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_bounds_overflow() {
+        let cadence = Cadence {
+            period: CalendarPeriod::Yearly,
+            missed_windows: u32::MAX,
+        };
+
+        assert!(cadence.bounds().is_err());
+    }
+}
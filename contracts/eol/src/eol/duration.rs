@@ -0,0 +1,242 @@
+use std::fmt;
+use std::str::FromStr;
+
+use cosmwasm_std::{StdError, StdResult, Timestamp};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{
+    InstanceType, Metadata, Schema, SchemaObject, StringValidation, SubschemaValidation,
+};
+use schemars::JsonSchema;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// Units accepted after the amount, largest first so the shortest form is written out
+const UNITS: [(&str, u64); 6] = [
+    ("w", 7 * 24 * 60 * 60 * NANOS_PER_SECOND),
+    ("d", 24 * 60 * 60 * NANOS_PER_SECOND),
+    ("h", 60 * 60 * NANOS_PER_SECOND),
+    ("m", 60 * NANOS_PER_SECOND),
+    ("s", NANOS_PER_SECOND),
+    ("ns", 1),
+];
+
+/// Span of time, nanosecond precise.
+///
+/// Deserializes from a number of seconds, or from a string holding an amount and
+/// one of the `w`, `d`, `h`, `m`, `s` or `ns` units, such as `"365d"` or `"6w"`.
+/// A string of digits alone is read as nanoseconds, which is how durations were
+/// serialized back when they were held in a `Timestamp`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration {
+    nanos: u64,
+}
+
+impl Duration {
+    pub const fn from_nanos(nanos: u64) -> Self {
+        Self { nanos }
+    }
+
+    pub fn from_seconds(seconds: u64) -> StdResult<Self> {
+        seconds
+            .checked_mul(NANOS_PER_SECOND)
+            .map(Self::from_nanos)
+            .ok_or_else(|| {
+                StdError::generic_err(format!("duration of {seconds} seconds overflows"))
+            })
+    }
+
+    pub const fn nanos(&self) -> u64 {
+        self.nanos
+    }
+
+    /// Whole seconds, dropping the nanos
+    pub const fn seconds(&self) -> u64 {
+        self.nanos / NANOS_PER_SECOND
+    }
+
+    /// Time `self` after `time`, failing past the latest representable timestamp
+    pub fn after(&self, time: Timestamp) -> StdResult<Timestamp> {
+        time.nanos()
+            .checked_add(self.nanos)
+            .map(Timestamp::from_nanos)
+            .ok_or_else(|| StdError::generic_err(format!("{self} after {time} overflows")))
+    }
+}
+
+impl FromStr for Duration {
+    type Err = StdError;
+
+    fn from_str(s: &str) -> StdResult<Self> {
+        let invalid = || StdError::generic_err(format!("invalid duration: {s}"));
+
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (amount, unit) = s.split_at(split);
+        if amount.is_empty() {
+            return Err(invalid());
+        }
+        let amount = amount.parse::<u64>().map_err(|_| invalid())?;
+
+        // legacy `Timestamp` nanos
+        if unit.is_empty() {
+            return Ok(Self::from_nanos(amount));
+        }
+
+        let (_, unit_nanos) = UNITS
+            .iter()
+            .find(|(name, _)| *name == unit)
+            .ok_or_else(invalid)?;
+
+        amount
+            .checked_mul(*unit_nanos)
+            .map(Self::from_nanos)
+            .ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (unit, unit_nanos) = UNITS
+            .iter()
+            .find(|(_, unit_nanos)| self.nanos.checked_rem(*unit_nanos) == Some(0))
+            .filter(|_| self.nanos > 0)
+            .unwrap_or(&("s", NANOS_PER_SECOND));
+
+        write!(f, "{}{unit}", self.nanos / unit_nanos)
+    }
+}
+
+impl Serialize for Duration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Duration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(DurationVisitor)
+    }
+}
+
+struct DurationVisitor;
+
+impl<'de> de::Visitor<'de> for DurationVisitor {
+    type Value = Duration;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a number of seconds or a duration string such as \"365d\"")
+    }
+
+    fn visit_u64<E: de::Error>(self, seconds: u64) -> Result<Self::Value, E> {
+        Duration::from_seconds(seconds).map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, seconds: i64) -> Result<Self::Value, E> {
+        let seconds = u64::try_from(seconds)
+            .map_err(|_| E::custom(format!("negative duration: {seconds}")))?;
+        self.visit_u64(seconds)
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        Duration::from_str(s).map_err(E::custom)
+    }
+}
+
+impl JsonSchema for Duration {
+    fn schema_name() -> String {
+        "Duration".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let string = SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                pattern: Some("^[0-9]+(w|d|h|m|s|ns)?$".to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+
+        SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                description: Some(
+                    "Number of seconds, or amount and unit (w, d, h, m, s, ns) such as \"365d\""
+                        .to_string(),
+                ),
+                ..Default::default()
+            })),
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![gen.subschema_for::<u64>(), string.into()]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{from_json, to_json_string};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::seconds("31536000", Duration::from_seconds(31_536_000).unwrap())]
+    #[case::days(r#""365d""#, Duration::from_seconds(365 * 24 * 60 * 60).unwrap())]
+    #[case::weeks(r#""6w""#, Duration::from_seconds(6 * 7 * 24 * 60 * 60).unwrap())]
+    #[case::hours(r#""12h""#, Duration::from_seconds(12 * 60 * 60).unwrap())]
+    #[case::minutes(r#""90m""#, Duration::from_seconds(90 * 60).unwrap())]
+    #[case::nanos(r#""1500ns""#, Duration::from_nanos(1_500))]
+    #[case::legacy_timestamp(r#""100000000001""#, Duration::from_nanos(100_000_000_001))]
+    fn test_deserialize(#[case] json: &str, #[case] expected: Duration) {
+        assert_eq!(from_json::<Duration>(json).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case::empty(r#""""#)]
+    #[case::no_amount(r#""d""#)]
+    #[case::unknown_unit(r#""3y""#)]
+    #[case::fraction(r#""1.5d""#)]
+    #[case::negative("-1")]
+    #[case::overflow(r#""99999999999999w""#)]
+    #[case::seconds_overflow("18446744073709551615")]
+    fn test_deserialize_invalid(#[case] json: &str) {
+        assert!(from_json::<Duration>(json).is_err());
+    }
+
+    #[test]
+    fn test_from_seconds_overflow() {
+        assert!(Duration::from_seconds(u64::MAX / NANOS_PER_SECOND).is_ok());
+        assert!(Duration::from_seconds(u64::MAX / NANOS_PER_SECOND + 1).is_err());
+    }
+
+    #[test]
+    fn test_after() {
+        let time = Timestamp::from_seconds(100);
+        assert_eq!(
+            Duration::from_seconds(20).unwrap().after(time).unwrap(),
+            Timestamp::from_seconds(120)
+        );
+        assert_eq!(
+            Duration::from_nanos(u64::MAX - time.nanos())
+                .after(time)
+                .unwrap(),
+            Timestamp::from_nanos(u64::MAX)
+        );
+        assert!(Duration::from_nanos(u64::MAX).after(time).is_err());
+    }
+
+    #[rstest]
+    #[case(Duration::from_seconds(0).unwrap(), "0s")]
+    #[case(Duration::from_seconds(100).unwrap(), "100s")]
+    #[case(Duration::from_seconds(120).unwrap(), "2m")]
+    #[case(Duration::from_seconds(365 * 24 * 60 * 60).unwrap(), "365d")]
+    #[case(Duration::from_seconds(6 * 7 * 24 * 60 * 60).unwrap(), "6w")]
+    #[case(Duration::from_nanos(100_000_000_001), "100000000001ns")]
+    fn test_serialize_roundtrip(#[case] duration: Duration, #[case] expected: &str) {
+        let json = to_json_string(&duration).unwrap();
+        assert_eq!(json, format!("\"{expected}\""));
+        assert_eq!(from_json::<Duration>(&json).unwrap(), duration);
+    }
+}
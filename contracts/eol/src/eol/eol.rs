@@ -1,45 +1,172 @@
-use crate::{
-    eol::error::EOLError,
-};
+use crate::eol::error::EOLError;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Coin, Coins, Timestamp, Uint128};
+use cosmwasm_std::{Addr, Api, Coin, HexBinary, Timestamp};
 use EOLError::TimeInBoundsError;
 
+use super::cadence::Cadence;
+use super::duration::Duration;
 use super::error::EOLResult;
+use super::fee::FeeSpending;
+use super::params::{beneficiary_commitment, BeneficiaryKey};
+use super::withdrawal::Withdrawals;
 
 /// State for tracking EOL
 #[cw_serde]
 #[derive(Default)]
 pub struct EOL {
-    /// Amount of time user if inactive can this account be used
-    pub inactivity_time_period: Timestamp,
+    /// Amount of time user if inactive can this account be used.
+    /// Stored as `inactivity_time_period` by 1.0.0, rewritten on migration to 1.1.0.
+    #[serde(alias = "inactivity_time_period")]
+    pub inactivity_period: Duration,
+
+    /// Calendar windows the owner must check in within, in place of the inactivity period
+    #[serde(default)]
+    pub cadence: Option<Cadence>,
 
     /// The last time the account spent
     /// This is used to check if we are in a new period
     pub last_spent_at: Timestamp,
 
+    /// Commitments of the beneficiaries that are yet to reveal their address
+    #[serde(default)]
+    pub beneficiary_commitments: Vec<HexBinary>,
+
+    /// Beneficiaries revealed against their commitment
+    #[serde(default)]
+    pub beneficiaries: Vec<RevealedBeneficiary>,
+
+    #[serde(default)]
+    pub status: EOLStatus,
+
+    /// Fees paid out of the account for beneficiaries in the current budget period
+    #[serde(default)]
+    pub fee_spending: FeeSpending,
+
+    /// Reserve resolved against the balances snapshotted
+    /// when beneficiaries first used the account
+    #[serde(default)]
+    pub reserved: Option<Vec<Coin>>,
+
+    /// Value withdrawn by beneficiaries in the current withdrawal cap period
+    #[serde(default)]
+    pub withdrawals: Withdrawals,
+
+    /// What left the account while it still had stake, capped by the staking preset
+    #[serde(default)]
+    pub staked_outflows: Vec<Coin>,
+}
+
+/// Committed beneficiary along with the key it signs with
+#[cw_serde]
+pub struct RevealedBeneficiary {
+    pub address: Addr,
+    pub key: BeneficiaryKey,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum EOLStatus {
+    #[default]
+    Active,
+    /// A beneficiary took the account over
+    Claimed { at: Timestamp },
 }
 
 impl EOL {
-    pub fn new(inactivity_period: Timestamp, last_spent: Timestamp) -> Self {
+    pub fn new(inactivity_period: Duration, last_spent: Timestamp) -> Self {
         Self {
-            inactivity_time_period: inactivity_period,
+            inactivity_period,
             last_spent_at: last_spent, // should be block.Time at initiation
+            ..Self::default()
         }
     }
 
-    pub fn update(
+    pub fn with_beneficiary_commitments(mut self, commitments: Vec<HexBinary>) -> Self {
+        self.beneficiary_commitments = commitments;
+        self
+    }
+
+    pub fn with_cadence(mut self, cadence: Option<Cadence>) -> Self {
+        self.cadence = cadence;
+        self
+    }
+
+    /// Whether `address` and `salt` open one of the pending beneficiary commitments
+    pub fn is_committed_beneficiary(&self, address: &str, salt: &[u8]) -> bool {
+        self.beneficiary_commitments
+            .contains(&beneficiary_commitment(address, salt))
+    }
+
+    /// Reveal a committed beneficiary, storing its plaintext address and the key
+    /// it derives from in place of the commitment it opens
+    pub fn reveal_beneficiary(
         &mut self,
-        last_spent_at: Timestamp,
-    ) -> &mut Self {
+        address: Addr,
+        key: BeneficiaryKey,
+        salt: &[u8],
+    ) -> EOLResult<&mut Self> {
+        if !key.derives_address(address.as_str()) {
+            return Err(EOLError::BeneficiaryKeyMismatch {
+                address: address.to_string(),
+            });
+        }
+
+        let commitment = beneficiary_commitment(address.as_str(), salt);
+        let position = self
+            .beneficiary_commitments
+            .iter()
+            .position(|c| c == &commitment)
+            .ok_or_else(|| EOLError::UnknownBeneficiaryCommitment {
+                address: address.to_string(),
+            })?;
+
+        self.beneficiary_commitments.remove(position);
+        self.beneficiaries
+            .push(RevealedBeneficiary { address, key });
+
+        Ok(self)
+    }
+
+    /// Address of the revealed beneficiary whose key produced `signature` over `sign_doc`, if any
+    pub fn signing_beneficiary(
+        &self,
+        api: &dyn Api,
+        sign_doc: &[u8],
+        signature: &[u8],
+    ) -> Option<&Addr> {
+        self.beneficiaries
+            .iter()
+            .find(|beneficiary| beneficiary.key.verify(api, sign_doc, signature))
+            .map(|beneficiary| &beneficiary.address)
+    }
+
+    pub fn update(&mut self, last_spent_at: Timestamp) -> &mut Self {
         self.last_spent_at = last_spent_at;
 
         self
     }
 
+    pub fn claim(&mut self, at: Timestamp) -> &mut Self {
+        if self.status == EOLStatus::Active {
+            self.status = EOLStatus::Claimed { at };
+        }
+
+        self
+    }
+
+    pub fn ensure_not_claimed(&self) -> EOLResult<()> {
+        match self.status {
+            EOLStatus::Active => Ok(()),
+            EOLStatus::Claimed { at } => Err(EOLError::AlreadyClaimed { claimed_at: at }),
+        }
+    }
+
     /// ensure that the value spent in the period is not over the limit
     pub fn ensure_out_of_limit(&self, curr_time: Timestamp) -> EOLResult<()> {
-        let eol_time = self.last_spent_at.plus_seconds(self.inactivity_time_period.seconds());
+        let eol_time = match &self.cadence {
+            Some(cadence) => cadence.dormant_at(self.last_spent_at)?,
+            None => self.inactivity_period.after(self.last_spent_at)?,
+        };
         if curr_time.le(&eol_time.clone()) {
             Err(TimeInBoundsError {
                 out_of_bounds_limit: eol_time,
@@ -50,115 +177,111 @@ impl EOL {
     }
 }
 
-//
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//     use rstest::rstest;
-//
-//     #[rstest]
-//     #[case::no_delta(vec![], vec![], vec![])]
-//     #[case::no_delta(vec![Coin::new(100, "uosmo")], balances_before_spent.clone(), vec![])]
-//     #[case::no_delta(vec![Coin::new(100, "uosmo"), Coin::new(1023, "usomething")], balances_before_spent.clone(), vec![]
-//     )]
-//     #[case::receive(
-//         vec![Coin::new(100, "uosmo")],
-//         vec![Coin::new(100, "uosmo"), Coin::new(200, "usomething")],
-//         vec![]
-//     )]
-//     #[case::receive(
-//         vec![Coin::new(100, "uosmo")],
-//         vec![Coin::new(101, "uosmo"), Coin::new(200, "usomething")],
-//         vec![]
-//     )]
-//     #[case::spend(
-//         vec![Coin::new(100, "uosmo"), Coin::new(200, "usomething")],
-//         vec![Coin::new(99, "uosmo"), Coin::new(200, "usomething")],
-//         vec![Coin::new(1, "uosmo")]
-//     )]
-//     #[case::spend(
-//         vec![Coin::new(100, "uosmo"), Coin::new(200, "usomething")],
-//         vec![Coin::new(99, "uosmo"), Coin::new(199, "usomething")],
-//         vec![
-//             Coin::new(1, "uosmo"),
-//             Coin::new(1, "usomething"),
-//         ]
-//     )]
-//     #[case::spend(
-//         vec![Coin::new(100, "uosmo"), Coin::new(200, "usomething")],
-//         vec![Coin::new(99, "uosmo")],
-//         vec![
-//             Coin::new(1, "uosmo"),
-//             Coin::new(200, "usomething"),
-//         ]
-//     )]
-//     #[case::mixed(
-//         vec![Coin::new(100, "uosmo"), Coin::new(200, "usomething")],
-//         vec![Coin::new(99, "uosmo"), Coin::new(200, "usomething"), Coin::new(100, "uother")],
-//         vec![
-//             Coin::new(1, "uosmo"),
-//         ]
-//     )]
-//
-//     pub fn test_calculate_spent_coins(
-//         #[case] balances_before_spent: Vec<Coin>,
-//         #[case] balances_after_spent: Vec<Coin>,
-//         #[case] expected: Vec<Coin>,
-//     ) {
-//         let balances_before_spent = Coins::try_from(balances_before_spent).unwrap();
-//         let balances_after_spent = Coins::try_from(balances_after_spent).unwrap();
-//         let deltas = calculate_spent_coins(&balances_before_spent, &balances_after_spent).unwrap();
-//         let expected = Coins::try_from(expected).unwrap();
-//         assert_eq!(expected, deltas);
-//     }
-//
-//     #[rstest]
-//     #[case::no_delta(vec![], vec![], vec![])]
-//     #[case::no_delta(vec![Coin::new(100, "uosmo")], balances_before_spent.clone(), vec![])]
-//     #[case::no_delta(vec![Coin::new(100, "uosmo"), Coin::new(1023, "usomething")], balances_before_spent.clone(), vec![]
-//     )]
-//     #[case::receive(
-//         vec![Coin::new(100, "uosmo")],
-//         vec![Coin::new(100, "uosmo"), Coin::new(200, "usomething")],
-//         vec![Coin::new(200, "usomething")]
-//     )]
-//     #[case::receive(
-//         vec![Coin::new(100, "uosmo")],
-//         vec![Coin::new(101, "uosmo"), Coin::new(200, "usomething")],
-//         vec![Coin::new(1, "uosmo"), Coin::new(200, "usomething")]
-//     )]
-//     #[case::spend(
-//         vec![Coin::new(100, "uosmo"), Coin::new(200, "usomething")],
-//         vec![Coin::new(99, "uosmo"), Coin::new(200, "usomething")],
-//         vec![]
-//     )]
-//     #[case::spend(
-//         vec![Coin::new(100, "uosmo"), Coin::new(200, "usomething")],
-//         vec![Coin::new(99, "uosmo"), Coin::new(199, "usomething")],
-//         vec![]
-//     )]
-//     #[case::spend(
-//         vec![Coin::new(100, "uosmo"), Coin::new(200, "usomething")],
-//         vec![Coin::new(99, "uosmo")],
-//         vec![]
-//     )]
-//     #[case::mixed(
-//         vec![Coin::new(100, "uosmo"), Coin::new(200, "usomething")],
-//         vec![Coin::new(99, "uosmo"), Coin::new(200, "usomething"), Coin::new(100, "uother")],
-//         vec![
-//             Coin::new(100, "uother"),
-//         ]
-//     )]
-//     pub fn test_calculate_received_coins(
-//         #[case] balances_before_spent: Vec<Coin>,
-//         #[case] balances_after_spent: Vec<Coin>,
-//         #[case] expected: Vec<Coin>,
-//     ) {
-//         let balances_before_spent = Coins::try_from(balances_before_spent).unwrap();
-//         let balances_after_spent = Coins::try_from(balances_after_spent).unwrap();
-//         let deltas =
-//             calculate_received_coins(&balances_before_spent, &balances_after_spent).unwrap();
-//         let expected = Coins::try_from(expected).unwrap();
-//         assert_eq!(expected, deltas);
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eol::CalendarPeriod;
+    use cosmwasm_std::testing::MockApi;
+
+    // ed25519 test vector taken from cosmwasm-crypto, signing "72"
+    const ED25519_SIG: &str = "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00";
+    const ED25519_PUBKEY: &str = "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
+
+    #[test]
+    fn test_reveal_beneficiary() {
+        let heir = "osmo188m3859xgsjn7pzjjssmnagmnvyf08ggtmtd6x";
+        let key =
+            BeneficiaryKey::Ed25519(HexBinary::from_hex(ED25519_PUBKEY).unwrap().to_vec().into());
+        let mut eol = EOL::new(
+            Duration::from_seconds(100).unwrap(),
+            Timestamp::from_seconds(0),
+        )
+        .with_beneficiary_commitments(vec![
+            beneficiary_commitment("heir1", b"salt1"),
+            beneficiary_commitment(heir, b"salt2"),
+        ]);
+
+        assert!(eol.is_committed_beneficiary(heir, b"salt2"));
+        assert!(!eol.is_committed_beneficiary(heir, b"salt1"));
+
+        // wrong salt
+        assert_eq!(
+            eol.reveal_beneficiary(Addr::unchecked(heir), key.clone(), b"salt1")
+                .unwrap_err(),
+            EOLError::UnknownBeneficiaryCommitment {
+                address: heir.to_string()
+            }
+        );
+
+        // key of another address
+        assert_eq!(
+            eol.reveal_beneficiary(Addr::unchecked("heir1"), key.clone(), b"salt1")
+                .unwrap_err(),
+            EOLError::BeneficiaryKeyMismatch {
+                address: "heir1".to_string()
+            }
+        );
+
+        eol.reveal_beneficiary(Addr::unchecked(heir), key.clone(), b"salt2")
+            .unwrap();
+        assert_eq!(
+            eol.beneficiaries,
+            vec![RevealedBeneficiary {
+                address: Addr::unchecked(heir),
+                key: key.clone(),
+            }]
+        );
+        assert_eq!(
+            eol.beneficiary_commitments,
+            vec![beneficiary_commitment("heir1", b"salt1")]
+        );
+
+        // the revealed beneficiary signs with its key
+        let api = MockApi::default();
+        assert_eq!(
+            eol.signing_beneficiary(&api, &[0x72], &HexBinary::from_hex(ED25519_SIG).unwrap()),
+            Some(&Addr::unchecked(heir))
+        );
+        assert_eq!(eol.signing_beneficiary(&api, &[0x72], &[1, 2, 3]), None);
+
+        // commitment can only be opened once
+        assert!(eol
+            .reveal_beneficiary(Addr::unchecked(heir), key, b"salt2")
+            .is_err());
+    }
+
+    #[test]
+    fn test_ensure_out_of_limit_overflow() {
+        // an inactivity period running past the latest timestamp errors out rather than panic
+        let eol = EOL::new(Duration::from_nanos(u64::MAX), Timestamp::from_seconds(1));
+        assert!(matches!(
+            eol.ensure_out_of_limit(Timestamp::from_nanos(u64::MAX)),
+            Err(EOLError::Std(_))
+        ));
+    }
+
+    #[test]
+    fn test_ensure_out_of_limit_with_cadence() {
+        // 2024-02-15, checked in within February
+        let last_active = Timestamp::from_seconds(1_707_955_200);
+        let mut eol = EOL::new(
+            Duration::from_seconds(100).unwrap(),
+            Timestamp::from_seconds(0),
+        )
+        .with_cadence(Some(Cadence {
+            period: CalendarPeriod::Monthly,
+            missed_windows: 1,
+        }));
+        eol.update(last_active);
+
+        // March missed, dormant from April on regardless of the inactivity period
+        let april = Timestamp::from_seconds(1_711_929_600);
+        assert_eq!(
+            eol.ensure_out_of_limit(april.minus_seconds(1)).unwrap_err(),
+            TimeInBoundsError {
+                out_of_bounds_limit: april
+            }
+        );
+        assert!(eol.ensure_out_of_limit(april.plus_seconds(1)).is_ok());
+    }
+}
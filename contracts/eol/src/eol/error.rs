@@ -1,4 +1,4 @@
-use cosmwasm_std::Timestamp;
+use cosmwasm_std::{Timestamp, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -9,9 +9,145 @@ pub enum EOLError {
     #[error("Time is yet to be out of bounds {out_of_bounds_limit}")]
     TimeInBoundsError { out_of_bounds_limit: Timestamp },
 
+    #[error("No beneficiary commitment is opened by address {address}")]
+    UnknownBeneficiaryCommitment { address: String },
+
+    #[error("Beneficiary address {address} is not derived from the key revealed with it")]
+    BeneficiaryKeyMismatch { address: String },
+
+    #[error("Message {type_url} is not allowed for beneficiaries")]
+    MessageNotAllowed { type_url: String },
+
+    #[error("Recipient {recipient} is not an allowed destination")]
+    DestinationNotAllowed { recipient: String },
+
+    #[error("IBC transfers over channel {channel} are not allowed for beneficiaries")]
+    ChannelNotAllowed { channel: String },
+
+    #[error("Receiver {receiver} is not allowed on channel {channel}")]
+    IbcReceiverNotAllowed { channel: String, receiver: String },
+
+    #[error("Invalid fee payment: {reason}")]
+    InvalidFeePayment { reason: String },
+
+    #[error("Fee budget of {limit}{denom} exceeded")]
+    FeeBudgetExceeded { denom: String, limit: Uint128 },
+
+    #[error("Denom {denom} may not leave the account")]
+    DenomNotAllowed { denom: String },
+
+    #[error("Withdrawal cap of {limit} exceeded")]
+    WithdrawalCapExceeded { limit: Uint128 },
+
+    #[error("Contract {contract} may not be executed by beneficiaries")]
+    ContractNotAllowed { contract: String },
+
+    #[error(
+        "Entry point {entry_point} of contract {contract} may not be executed by beneficiaries"
+    )]
+    EntryPointNotAllowed {
+        contract: String,
+        entry_point: String,
+    },
+
+    #[error("Instantiations of code {code_id} carrying funds are not allowed for beneficiaries")]
+    FundedInstantiationNotAllowed { code_id: u64 },
+
+    #[error("Tokens of contract {contract} may not leave the account")]
+    AssetNotAllowed { contract: String },
+
+    #[error("Only transfers of contract {contract} tokens are allowed")]
+    AssetMsgNotAllowed { contract: String },
+
+    #[error("Transfer cap of {cap} tokens of contract {contract} exceeded")]
+    AssetCapExceeded { contract: String, cap: Uint128 },
+
+    #[error("Transfers of {denom} are capped at {cap}{denom} until the stake is unbonded")]
+    TransferCapExceeded { denom: String, cap: Uint128 },
+
+    #[error("Balance of {balance}{denom} would drop below the reserve of {reserve}{denom}")]
+    ReserveBreached {
+        denom: String,
+        reserve: Uint128,
+        balance: Uint128,
+    },
+
+    #[error("Message {type_url} could not be decoded for inspection")]
+    UndecodableMessage { type_url: String },
+
+    #[error("Messages nested deeper than {max_depth} levels can't be inspected")]
+    MsgNestingTooDeep { max_depth: usize },
+
+    #[error("Invalid takeover: {reason}")]
+    InvalidTakeover { reason: String },
+
+    #[error("Account has already been claimed at {claimed_at}")]
+    AlreadyClaimed { claimed_at: Timestamp },
+
     #[error("invalid request")]
     InvalidRequest {},
 }
 
-pub type EOLResult<T> = Result<T, EOLError>;
+impl EOLError {
+    pub fn message_not_allowed(type_url: &str) -> Self {
+        Self::MessageNotAllowed {
+            type_url: type_url.to_string(),
+        }
+    }
+
+    pub fn destination_not_allowed(recipient: &str) -> Self {
+        Self::DestinationNotAllowed {
+            recipient: recipient.to_string(),
+        }
+    }
+
+    pub fn channel_not_allowed(channel: &str) -> Self {
+        Self::ChannelNotAllowed {
+            channel: channel.to_string(),
+        }
+    }
+
+    pub fn denom_not_allowed(denom: &str) -> Self {
+        Self::DenomNotAllowed {
+            denom: denom.to_string(),
+        }
+    }
 
+    pub fn contract_not_allowed(contract: &str) -> Self {
+        Self::ContractNotAllowed {
+            contract: contract.to_string(),
+        }
+    }
+
+    pub fn asset_not_allowed(contract: &str) -> Self {
+        Self::AssetNotAllowed {
+            contract: contract.to_string(),
+        }
+    }
+
+    pub fn asset_msg_not_allowed(contract: &str) -> Self {
+        Self::AssetMsgNotAllowed {
+            contract: contract.to_string(),
+        }
+    }
+
+    pub fn invalid_fee_payment(reason: &str) -> Self {
+        Self::InvalidFeePayment {
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn undecodable_message(type_url: &str) -> Self {
+        Self::UndecodableMessage {
+            type_url: type_url.to_string(),
+        }
+    }
+
+    pub fn invalid_takeover(reason: &str) -> Self {
+        Self::InvalidTakeover {
+            reason: reason.to_string(),
+        }
+    }
+}
+
+pub type EOLResult<T> = Result<T, EOLError>;
@@ -0,0 +1,220 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ensure, Addr, Coin, Coins, Timestamp};
+use cw_authenticator::AuthenticationRequest;
+
+use super::duration::Duration;
+use super::error::{EOLError, EOLResult};
+use super::params::EOLParams;
+use super::EOL;
+
+/// Who covers the fees of beneficiary transactions
+#[cw_serde]
+#[derive(Default)]
+pub enum FeePolicy {
+    /// Fees may be paid out of the dormant account
+    #[default]
+    Account,
+    /// Fees must be covered by a fee grant from any address but the account
+    Granter,
+    /// Fees must be paid by a beneficiary signing the transaction
+    Beneficiary,
+}
+
+/// Cap on the fees the dormant account pays for beneficiaries in every `period`
+#[cw_serde]
+pub struct FeeBudget {
+    pub limit: Vec<Coin>,
+    pub period: Duration,
+}
+
+/// Fees the account paid for beneficiaries since `period_start`
+#[cw_serde]
+#[derive(Default)]
+pub struct FeeSpending {
+    pub period_start: Timestamp,
+    pub spent: Vec<Coin>,
+}
+
+impl FeeSpending {
+    /// Record `fee` as spent at `now`. A new period starts with the first
+    /// spending after the one being tracked is over, or with the very first one.
+    pub fn spend(
+        &mut self,
+        budget: &FeeBudget,
+        fee: &[Coin],
+        now: Timestamp,
+    ) -> EOLResult<&mut Self> {
+        if self.spent.is_empty() || now >= budget.period.after(self.period_start)? {
+            *self = Self {
+                period_start: now,
+                spent: vec![],
+            };
+        }
+
+        let mut spent = Coins::default();
+        for coin in self.spent.iter().chain(fee) {
+            spent.add(coin.clone())?;
+        }
+
+        for coin in spent.iter() {
+            let limit = budget
+                .limit
+                .iter()
+                .find(|limit| limit.denom == coin.denom)
+                .map(|limit| limit.amount)
+                .unwrap_or_default();
+
+            ensure!(
+                coin.amount <= limit,
+                EOLError::FeeBudgetExceeded {
+                    denom: coin.denom.clone(),
+                    limit,
+                }
+            );
+        }
+
+        self.spent = spent.into_vec();
+        Ok(self)
+    }
+}
+
+/// Whether the fees end up deducted from `account`
+pub fn paid_by_account(account: &Addr, fee_payer: &Addr, fee_granter: Option<&Addr>) -> bool {
+    fee_granter.unwrap_or(fee_payer) == account
+}
+
+impl EOLParams {
+    /// Ensure the fees of a beneficiary transaction are covered as the policy requires
+    /// and, when paid by the account, fit in what's left of the budget
+    pub fn ensure_fee_allowed(
+        &self,
+        eol: &EOL,
+        request: &AuthenticationRequest,
+        now: Timestamp,
+    ) -> EOLResult<()> {
+        let AuthenticationRequest {
+            account,
+            fee_payer,
+            fee_granter,
+            fee,
+            ..
+        } = request;
+
+        match self.fee_policy {
+            FeePolicy::Account => {}
+            FeePolicy::Granter => ensure!(
+                fee_granter
+                    .as_ref()
+                    .is_some_and(|fee_granter| fee_granter != account),
+                EOLError::invalid_fee_payment("fees must be covered by a fee granter")
+            ),
+            FeePolicy::Beneficiary => ensure!(
+                fee_granter.is_none()
+                    && fee_payer != account
+                    && request.signature_data.signers.contains(fee_payer),
+                EOLError::invalid_fee_payment("fees must be paid by the beneficiary")
+            ),
+        }
+
+        if let Some(budget) = &self.fee_budget {
+            if paid_by_account(account, fee_payer, fee_granter.as_ref()) {
+                eol.fee_spending.clone().spend(budget, fee, now)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{coin, coins, Uint128};
+
+    fn budget() -> FeeBudget {
+        FeeBudget {
+            limit: coins(1_000, "uosmo"),
+            period: Duration::from_seconds(100).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_fee_spending() {
+        let budget = budget();
+        let mut spending = FeeSpending::default();
+        let start = Timestamp::from_seconds(1_000);
+
+        spending
+            .spend(&budget, &coins(600, "uosmo"), start)
+            .unwrap();
+        assert_eq!(
+            spending,
+            FeeSpending {
+                period_start: start,
+                spent: coins(600, "uosmo"),
+            }
+        );
+
+        // over the budget within the same period
+        assert_eq!(
+            spending
+                .clone()
+                .spend(&budget, &coins(401, "uosmo"), start.plus_seconds(99))
+                .unwrap_err(),
+            EOLError::FeeBudgetExceeded {
+                denom: "uosmo".to_string(),
+                limit: Uint128::new(1_000),
+            }
+        );
+
+        // denom without budget
+        assert_eq!(
+            spending
+                .clone()
+                .spend(&budget, &[coin(1, "uion")], start)
+                .unwrap_err(),
+            EOLError::FeeBudgetExceeded {
+                denom: "uion".to_string(),
+                limit: Uint128::zero(),
+            }
+        );
+
+        spending
+            .spend(&budget, &coins(400, "uosmo"), start.plus_seconds(99))
+            .unwrap();
+        assert_eq!(spending.spent, coins(1_000, "uosmo"));
+
+        // budget is restored in the next period
+        spending
+            .spend(&budget, &coins(1_000, "uosmo"), start.plus_seconds(100))
+            .unwrap();
+        assert_eq!(
+            spending,
+            FeeSpending {
+                period_start: start.plus_seconds(100),
+                spent: coins(1_000, "uosmo"),
+            }
+        );
+
+        // a period running past the latest timestamp errors out rather than panic
+        let endless = FeeBudget {
+            period: Duration::from_nanos(u64::MAX),
+            ..budget
+        };
+        assert!(matches!(
+            spending.spend(&endless, &coins(1, "uosmo"), start.plus_seconds(101)),
+            Err(EOLError::Std(_))
+        ));
+    }
+
+    #[test]
+    fn test_paid_by_account() {
+        let account = Addr::unchecked("account");
+        let other = Addr::unchecked("other");
+
+        assert!(paid_by_account(&account, &account, None));
+        assert!(paid_by_account(&account, &other, Some(&account)));
+        assert!(!paid_by_account(&account, &other, None));
+        assert!(!paid_by_account(&account, &account, Some(&other)));
+    }
+}
@@ -0,0 +1,127 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::ensure;
+use cw_authenticator::Any;
+use osmosis_std::types::ibc::applications::transfer::v1::MsgTransfer;
+
+use super::error::{EOLError, EOLResult};
+use super::params::EOLParams;
+
+/// IBC channel beneficiaries may transfer funds over
+#[cw_serde]
+pub struct IbcChannelRule {
+    pub source_channel: String,
+
+    /// Receivers on the counterparty chain, any receiver when unset
+    #[serde(default)]
+    pub receivers: Option<Vec<String>>,
+}
+
+impl EOLParams {
+    /// Ensure `msg`, if it is an IBC transfer, goes over an allowed channel to an allowed receiver
+    pub fn ensure_ibc_transfer_allowed(&self, msg: &Any) -> EOLResult<()> {
+        let Some(ibc_channels) = &self.ibc_channels else {
+            return Ok(());
+        };
+        if msg.type_url != MsgTransfer::TYPE_URL {
+            return Ok(());
+        }
+
+        let transfer = MsgTransfer::try_from(msg.value.clone())
+            .map_err(|_| EOLError::undecodable_message(&msg.type_url))?;
+
+        let rule = ibc_channels
+            .iter()
+            .find(|rule| rule.source_channel == transfer.source_channel)
+            .ok_or_else(|| EOLError::channel_not_allowed(&transfer.source_channel))?;
+
+        ensure!(
+            rule.receivers
+                .as_ref()
+                .is_none_or(|receivers| receivers.contains(&transfer.receiver)),
+            EOLError::IbcReceiverNotAllowed {
+                channel: transfer.source_channel,
+                receiver: transfer.receiver,
+            }
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::Binary;
+    use rstest::rstest;
+
+    fn transfer(source_channel: &str, receiver: &str) -> Any {
+        Any {
+            type_url: MsgTransfer::TYPE_URL.to_string(),
+            value: MsgTransfer {
+                source_port: "transfer".to_string(),
+                source_channel: source_channel.to_string(),
+                receiver: receiver.to_string(),
+                ..MsgTransfer::default()
+            }
+            .into(),
+        }
+    }
+
+    fn params() -> EOLParams {
+        EOLParams {
+            ibc_channels: Some(vec![
+                IbcChannelRule {
+                    source_channel: "channel-0".to_string(),
+                    receivers: None,
+                },
+                IbcChannelRule {
+                    source_channel: "channel-1".to_string(),
+                    receivers: Some(vec!["cosmos1heir".to_string()]),
+                },
+            ]),
+            ..EOLParams::default()
+        }
+    }
+
+    #[rstest]
+    #[case::any_receiver("channel-0", "cosmos1other", Ok(()))]
+    #[case::allowed_receiver("channel-1", "cosmos1heir", Ok(()))]
+    #[case::receiver_not_allowed(
+        "channel-1",
+        "cosmos1other",
+        Err(EOLError::IbcReceiverNotAllowed {
+            channel: "channel-1".to_string(),
+            receiver: "cosmos1other".to_string(),
+        })
+    )]
+    #[case::channel_not_allowed(
+        "channel-2",
+        "cosmos1heir",
+        Err(EOLError::channel_not_allowed("channel-2"))
+    )]
+    fn test_ensure_ibc_transfer_allowed(
+        #[case] source_channel: &str,
+        #[case] receiver: &str,
+        #[case] expected: EOLResult<()>,
+    ) {
+        assert_eq!(
+            params().ensure_ibc_transfer_allowed(&transfer(source_channel, receiver)),
+            expected
+        );
+
+        // unrestricted without channel rules
+        assert_eq!(
+            EOLParams::default().ensure_ibc_transfer_allowed(&transfer(source_channel, receiver)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_ignores_other_msgs() {
+        let msg = Any {
+            type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+            value: Binary::default(),
+        };
+        assert_eq!(params().ensure_ibc_transfer_allowed(&msg), Ok(()));
+    }
+}
@@ -1,10 +1,37 @@
+pub mod asset;
+pub mod cadence;
+pub mod duration;
+pub mod eol;
 pub mod error;
+pub mod fee;
+pub mod ibc;
 pub mod params;
-pub mod eol;
+pub mod policy;
+pub mod reserve;
+pub mod session;
+pub mod staking;
+pub mod takeover;
+pub mod transfer;
+pub mod unpack;
+pub mod wasm;
+pub mod withdrawal;
 
 use cosmwasm_std::Addr;
 use cw_storage_plus::Map;
 
+pub use self::eol::{EOLStatus, RevealedBeneficiary, EOL};
+pub use asset::{AssetRule, AssetTransfers};
+pub use cadence::{Cadence, CalendarPeriod};
+pub use duration::Duration;
+pub use fee::{FeeBudget, FeePolicy, FeeSpending};
+pub use ibc::IbcChannelRule;
+pub use params::{BeneficiaryKey, EOLParams};
+pub use reserve::Reserve;
+pub use session::{Session, Signer};
+pub use staking::StakingPreset;
+pub use wasm::ContractRule;
+pub use withdrawal::{WithdrawalCap, Withdrawals};
+
 pub type EOLStore<'a> = Map<'a, EOLKey<'a>, eol::EOL>;
 
 /// SpendingKey is a key for the spending storage.
@@ -12,3 +39,14 @@ pub type EOLStore<'a> = Map<'a, EOLKey<'a>, eol::EOL>;
 /// allows multiple spend limits per account.
 pub type EOLKey<'a> = (&'a Addr, &'a str);
 
+pub type SessionStore<'a> = Map<'a, SessionKey<'a>, Session>;
+
+/// SessionKey is a tuple of (account, authenticator_id, msg_index)
+/// since every message of a transaction is authenticated separately.
+pub type SessionKey<'a> = (&'a Addr, &'a str, u64);
+
+pub type AssetTransfersStore<'a> = Map<'a, AssetTransfersKey<'a>, AssetTransfers>;
+
+/// AssetTransfersKey is a tuple of (account, authenticator_id, beneficiary)
+/// where beneficiary is the [`Signer::id`] of the beneficiary.
+pub type AssetTransfersKey<'a> = (&'a Addr, &'a str, &'a str);
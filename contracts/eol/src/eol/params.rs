@@ -1,9 +1,523 @@
+use bech32::FromBase32;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Timestamp, Uint128};
+use cosmwasm_std::{ensure, Api, Binary, HexBinary, StdError, StdResult};
+use cw_authenticator::sha256;
+use ripemd::{Digest, Ripemd160};
 
+use super::asset::AssetRule;
+use super::cadence::Cadence;
+use super::duration::Duration;
+use super::fee::{FeeBudget, FeePolicy};
+use super::ibc::IbcChannelRule;
+use super::reserve::Reserve;
+use super::staking::StakingPreset;
+use super::wasm::ContractRule;
+use super::withdrawal::WithdrawalCap;
+
+/// Compressed or uncompressed secp256k1 public key length
+const SECP256K1_PUBKEY_LENGTHS: [usize; 2] = [33, 65];
+const SECP256K1_COMPRESSED_PUBKEY_LENGTH: usize = 33;
+const ED25519_PUBKEY_LENGTH: usize = 32;
+/// Length of the hash an account address is the bech32 encoding of
+const ADDRESS_HASH_LENGTH: usize = 20;
+const COMMITMENT_LENGTH: usize = 32;
 
 #[cw_serde]
+#[derive(Default)]
 pub struct EOLParams {
-    pub inactivity_period: Timestamp,
+    /// Public key the owner signs with, in the same forms as beneficiary keys.
+    /// Required when the authenticator is added, without it no signature is
+    /// authenticated as the owner's.
+    #[serde(default)]
+    pub owner_key: Option<BeneficiaryKey>,
+
+    /// How long the owner must stay inactive before beneficiaries may use the account
+    pub inactivity_period: Duration,
+
+    /// Calendar windows the owner must check in within, in place of the inactivity period
+    #[serde(default)]
+    pub cadence: Option<Cadence>,
+
+    /// Public keys allowed to sign for the account once it is dormant
+    #[serde(default)]
+    pub beneficiaries: Vec<BeneficiaryKey>,
+
+    /// Salted hash commitments of beneficiary addresses, see [`beneficiary_commitment`].
+    /// The addresses stay private until revealed once the account is dormant.
+    #[serde(default)]
+    pub beneficiary_commitments: Vec<HexBinary>,
+
+    /// Let beneficiaries add or remove the account's authenticators.
+    /// Off by default since it would let them strip the owner's other protections.
+    #[serde(default)]
+    pub allow_authenticator_management: bool,
+
+    /// Let a beneficiary holding a secp256k1 key take the dormant account over
+    /// in a single transaction that registers their key and removes all the others.
+    /// The authenticator stays to lock out the owner, so it must not be nested
+    /// in a composite authenticator.
+    #[serde(default)]
+    pub allow_takeover: bool,
+
+    /// Recipients beneficiaries may transfer funds to, by bank send, over IBC, as funds of
+    /// a contract execution or as cw20 and cw721 tokens. Funded instantiations and token
+    /// allowances are rejected while set. Transfers are unrestricted when unset.
+    #[serde(default)]
+    pub allowed_destinations: Option<Vec<String>>,
+
+    /// IBC channels beneficiaries may transfer funds over, any channel when unset
+    #[serde(default)]
+    pub ibc_channels: Option<Vec<IbcChannelRule>>,
+
+    /// Who covers the fees of beneficiary transactions
+    #[serde(default)]
+    pub fee_policy: FeePolicy,
+
+    /// Cap on the fees paid out of the account for beneficiary transactions
+    #[serde(default)]
+    pub fee_budget: Option<FeeBudget>,
+
+    /// Balances beneficiaries must leave in the account
+    #[serde(default)]
+    pub reserve: Vec<Reserve>,
+
+    /// Denoms beneficiaries may move out of the account, any denom when unset
+    #[serde(default)]
+    pub allowed_denoms: Option<Vec<String>>,
+
+    /// Denoms beneficiaries may never move out of the account
+    #[serde(default)]
+    pub denied_denoms: Vec<String>,
+
+    /// Cap on the value beneficiaries may withdraw, priced by the contract's TWAP config.
+    /// Moving denoms the admin doesn't track is rejected since they can't be valued.
+    #[serde(default)]
+    pub withdrawal_cap: Option<WithdrawalCap>,
+
+    /// Let beneficiaries unwind the account's stake while capping transfers until it's unbonded
+    #[serde(default)]
+    pub staking: Option<StakingPreset>,
+
+    /// Contracts beneficiaries may execute, any contract when unset
+    #[serde(default)]
+    pub allowed_contracts: Option<Vec<ContractRule>>,
+
+    /// cw20 contracts whose tokens beneficiaries may transfer, send or burn, any contract
+    /// when unset. Once set, together with `cw721_rules`, allowances and approvals are
+    /// rejected and the contracts listed may only be sent transfers.
+    #[serde(default)]
+    pub cw20_rules: Option<Vec<AssetRule>>,
+
+    /// cw721 contracts whose NFTs beneficiaries may transfer, send or burn,
+    /// any contract when unset
+    #[serde(default)]
+    pub cw721_rules: Option<Vec<AssetRule>>,
 }
 
+impl EOLParams {
+    pub fn validate(&self) -> StdResult<()> {
+        let owner_key = self
+            .owner_key
+            .as_ref()
+            .ok_or_else(|| StdError::generic_err("owner key is required"))?;
+        owner_key.validate().map_err(|_| {
+            StdError::generic_err(format!("invalid owner public key: {}", owner_key.pubkey()))
+        })?;
+        ensure!(
+            !self.beneficiaries.contains(owner_key),
+            StdError::generic_err("owner key must not be a beneficiary key")
+        );
+
+        self.beneficiaries
+            .iter()
+            .try_for_each(BeneficiaryKey::validate)?;
+
+        ensure!(
+            !self.allow_takeover
+                || self.beneficiaries.iter().all(|key| match key {
+                    BeneficiaryKey::Secp256k1(pubkey) => {
+                        pubkey.len() == SECP256K1_COMPRESSED_PUBKEY_LENGTH
+                    }
+                    BeneficiaryKey::Ed25519(_) => true,
+                }),
+            StdError::generic_err("takeover requires compressed secp256k1 beneficiary keys")
+        );
+
+        for commitment in self.beneficiary_commitments.iter() {
+            ensure!(
+                commitment.len() == COMMITMENT_LENGTH,
+                StdError::generic_err(format!("invalid beneficiary commitment: {commitment}"))
+            );
+        }
+
+        self.reserve.iter().try_for_each(Reserve::validate)?;
+
+        if let Some(cadence) = &self.cadence {
+            cadence.validate()?;
+        }
+
+        if let Some(withdrawal_cap) = &self.withdrawal_cap {
+            ensure!(
+                withdrawal_cap.period.nanos() > 0,
+                StdError::generic_err("withdrawal cap period must not be zero")
+            );
+        }
+
+        if let Some(fee_budget) = &self.fee_budget {
+            ensure!(
+                fee_budget.period.nanos() > 0,
+                StdError::generic_err("fee budget period must not be zero")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Whether balances must be snapshotted before beneficiary transactions
+    /// to tell what left the account
+    pub fn tracks_balances(&self) -> bool {
+        self.restricts_denoms() || self.withdrawal_cap.is_some() || self.staking.is_some()
+    }
+
+    /// Whether the owner's key produced `signature` over `sign_doc`
+    pub fn signed_by_owner(&self, api: &dyn Api, sign_doc: &[u8], signature: &[u8]) -> bool {
+        self.owner_key
+            .as_ref()
+            .is_some_and(|key| key.verify(api, sign_doc, signature))
+    }
+
+    /// Index of the beneficiary whose key produced `signature` over `sign_doc`, if any
+    pub fn signing_beneficiary(
+        &self,
+        api: &dyn Api,
+        sign_doc: &[u8],
+        signature: &[u8],
+    ) -> Option<u32> {
+        self.beneficiaries
+            .iter()
+            .position(|key| key.verify(api, sign_doc, signature))
+            .map(|index| index as u32)
+    }
+
+    /// Key the beneficiary at `index` takes the account over with, only compressed
+    /// secp256k1 keys can be registered as signature verification
+    pub fn takeover_key(&self, index: u32) -> Option<&Binary> {
+        match self.beneficiaries.get(index as usize) {
+            Some(BeneficiaryKey::Secp256k1(pubkey))
+                if self.allow_takeover && pubkey.len() == SECP256K1_COMPRESSED_PUBKEY_LENGTH =>
+            {
+                Some(pubkey)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Commitment to a beneficiary address: `sha256(address || salt)`
+pub fn beneficiary_commitment(address: &str, salt: &[u8]) -> HexBinary {
+    sha256(&[address.as_bytes(), salt].concat()).into()
+}
+
+#[cw_serde]
+pub enum BeneficiaryKey {
+    Secp256k1(Binary),
+    Ed25519(Binary),
+}
+
+impl BeneficiaryKey {
+    pub fn validate(&self) -> StdResult<()> {
+        let valid = match self {
+            BeneficiaryKey::Secp256k1(pubkey) => SECP256K1_PUBKEY_LENGTHS.contains(&pubkey.len()),
+            BeneficiaryKey::Ed25519(pubkey) => pubkey.len() == ED25519_PUBKEY_LENGTH,
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(StdError::generic_err(format!(
+                "invalid beneficiary public key: {}",
+                self.pubkey()
+            )))
+        }
+    }
+
+    pub fn pubkey(&self) -> &Binary {
+        match self {
+            BeneficiaryKey::Secp256k1(pubkey) | BeneficiaryKey::Ed25519(pubkey) => pubkey,
+        }
+    }
+
+    /// Whether `address` is the account address of this key, whatever its bech32 prefix
+    pub fn derives_address(&self, address: &str) -> bool {
+        let Ok((_, data, _)) = bech32::decode(address) else {
+            return false;
+        };
+
+        self.address_hash()
+            .is_some_and(|hash| Vec::<u8>::from_base32(&data).is_ok_and(|bytes| bytes == hash))
+    }
+
+    /// Hash an account address encodes, as derived by the cosmos sdk.
+    /// Addresses are derived from compressed secp256k1 keys only.
+    fn address_hash(&self) -> Option<Vec<u8>> {
+        match self {
+            BeneficiaryKey::Secp256k1(pubkey)
+                if pubkey.len() == SECP256K1_COMPRESSED_PUBKEY_LENGTH =>
+            {
+                Some(Ripemd160::digest(sha256(pubkey)).to_vec())
+            }
+            BeneficiaryKey::Secp256k1(_) => None,
+            BeneficiaryKey::Ed25519(pubkey) => Some(sha256(pubkey)[..ADDRESS_HASH_LENGTH].to_vec()),
+        }
+    }
+
+    /// Verify `signature` over the sign mode direct bytes.
+    /// Malformed signatures are treated as not matching since the same request
+    /// may carry a signature meant for a different key.
+    pub fn verify(&self, api: &dyn Api, sign_doc: &[u8], signature: &[u8]) -> bool {
+        match self {
+            BeneficiaryKey::Secp256k1(pubkey) => {
+                api.secp256k1_verify(&sha256(sign_doc), signature, pubkey)
+            }
+            BeneficiaryKey::Ed25519(pubkey) => api.ed25519_verify(sign_doc, signature, pubkey),
+        }
+        .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockApi;
+
+    // test vectors taken from cosmwasm-crypto
+    const SECP256K1_MSG: &str = "5c868fedb8026979ebd26f1ba07c27eedf4ff6d10443505a96ecaf21ba8c4f0937b3cd23ffdc3dd429d4cd1905fb8dbcceeff1350020e18b58d2ba70887baa3a9b783ad30d3fbf210331cdd7df8d77defa398cdacdfc2e359c7ba4cae46bb74401deb417f8b912a1aa966aeeba9c39c7dd22479ae2b30719dca2f2206c5eb4b7";
+    const SECP256K1_SIG: &str = "207082eb2c3dfa0b454e0906051270ba4074ac93760ba9e7110cd9471475111151eb0dbbc9920e72146fb564f99d039802bf6ef2561446eb126ef364d21ee9c4";
+    const SECP256K1_PUBKEY: &str = "04051c1ee2190ecfb174bfe4f90763f2b4ff7517b70a2aec1876ebcfd644c4633fb03f3cfbd94b1f376e34592d9d41ccaf640bb751b00a1fadeb0c01157769eb73";
+    const SECP256K1_COMPRESSED_PUBKEY: &str =
+        "03051c1ee2190ecfb174bfe4f90763f2b4ff7517b70a2aec1876ebcfd644c4633f";
+
+    const ED25519_MSG: &str = "72";
+    const ED25519_SIG: &str = "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00";
+    const ED25519_PUBKEY: &str = "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
+
+    fn hex(s: &str) -> Binary {
+        HexBinary::from_hex(s).unwrap().to_vec().into()
+    }
+
+    fn params() -> EOLParams {
+        EOLParams {
+            owner_key: Some(BeneficiaryKey::Secp256k1(Binary::from(vec![2; 33]))),
+            inactivity_period: Duration::from_seconds(100).unwrap(),
+            beneficiaries: vec![
+                BeneficiaryKey::Ed25519(hex(ED25519_PUBKEY)),
+                BeneficiaryKey::Secp256k1(hex(SECP256K1_PUBKEY)),
+            ],
+            beneficiary_commitments: vec![beneficiary_commitment("heir", b"salt")],
+            ..EOLParams::default()
+        }
+    }
+
+    #[test]
+    fn test_signing_beneficiary() {
+        let api = MockApi::default();
+        let params = params();
+
+        assert_eq!(
+            params.signing_beneficiary(&api, &hex(ED25519_MSG), &hex(ED25519_SIG)),
+            Some(0)
+        );
+        assert_eq!(
+            params.signing_beneficiary(&api, &hex(SECP256K1_MSG), &hex(SECP256K1_SIG)),
+            Some(1)
+        );
+
+        // signature over another message
+        assert_eq!(
+            params.signing_beneficiary(&api, &hex(SECP256K1_MSG), &hex(ED25519_SIG)),
+            None
+        );
+
+        // malformed signature
+        assert_eq!(
+            params.signing_beneficiary(&api, &hex(ED25519_MSG), &[1, 2, 3]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_derives_address() {
+        let secp256k1 = BeneficiaryKey::Secp256k1(hex(SECP256K1_COMPRESSED_PUBKEY));
+        let ed25519 = BeneficiaryKey::Ed25519(hex(ED25519_PUBKEY));
+
+        assert!(secp256k1.derives_address("osmo14g0cww50zdysxrxfpjvsrf566eehqmmk78eyhk"));
+        assert!(secp256k1.derives_address("cosmos14g0cww50zdysxrxfpjvsrf566eehqmmkku25py"));
+        assert!(ed25519.derives_address("osmo188m3859xgsjn7pzjjssmnagmnvyf08ggtmtd6x"));
+
+        // another key's address
+        assert!(!secp256k1.derives_address("osmo188m3859xgsjn7pzjjssmnagmnvyf08ggtmtd6x"));
+
+        // addresses are derived from compressed keys only
+        assert!(!BeneficiaryKey::Secp256k1(hex(SECP256K1_PUBKEY))
+            .derives_address("osmo14g0cww50zdysxrxfpjvsrf566eehqmmk78eyhk"));
+
+        // not bech32
+        assert!(!ed25519.derives_address("heir"));
+    }
+
+    #[test]
+    fn test_signed_by_owner() {
+        let api = MockApi::default();
+        let params = EOLParams {
+            owner_key: Some(BeneficiaryKey::Secp256k1(hex(SECP256K1_COMPRESSED_PUBKEY))),
+            ..params()
+        };
+
+        assert!(params.signed_by_owner(&api, &hex(SECP256K1_MSG), &hex(SECP256K1_SIG)));
+
+        // a beneficiary's signature
+        assert!(!params.signed_by_owner(&api, &hex(ED25519_MSG), &hex(ED25519_SIG)));
+
+        // no owner key to check against
+        let params = EOLParams {
+            owner_key: None,
+            ..params
+        };
+        assert!(!params.signed_by_owner(&api, &hex(SECP256K1_MSG), &hex(SECP256K1_SIG)));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(params().validate(), Ok(()));
+
+        let invalid = EOLParams {
+            beneficiaries: vec![BeneficiaryKey::Ed25519(hex(SECP256K1_PUBKEY))],
+            ..params()
+        };
+        assert!(invalid.validate().is_err());
+
+        let invalid = EOLParams {
+            beneficiary_commitments: vec![HexBinary::from(b"heir".to_vec())],
+            ..params()
+        };
+        assert!(invalid.validate().is_err());
+
+        let invalid = EOLParams {
+            fee_budget: Some(FeeBudget {
+                limit: vec![],
+                period: Duration::from_seconds(0).unwrap(),
+            }),
+            ..params()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_takeover_key() {
+        let params = EOLParams {
+            beneficiaries: vec![
+                BeneficiaryKey::Ed25519(hex(ED25519_PUBKEY)),
+                BeneficiaryKey::Secp256k1(hex(SECP256K1_PUBKEY)),
+                BeneficiaryKey::Secp256k1(hex(SECP256K1_COMPRESSED_PUBKEY)),
+            ],
+            allow_takeover: true,
+            ..params()
+        };
+
+        assert_eq!(params.takeover_key(0), None);
+        // the chain only registers compressed keys as signature verification
+        assert_eq!(params.takeover_key(1), None);
+        assert_eq!(
+            params.takeover_key(2),
+            Some(&hex(SECP256K1_COMPRESSED_PUBKEY))
+        );
+        assert_eq!(params.takeover_key(3), None);
+
+        assert_eq!(
+            params.validate(),
+            Err(StdError::generic_err(
+                "takeover requires compressed secp256k1 beneficiary keys"
+            ))
+        );
+        assert_eq!(
+            EOLParams {
+                allow_takeover: false,
+                ..params
+            }
+            .validate(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_validate_owner_key() {
+        assert_eq!(
+            EOLParams {
+                owner_key: None,
+                ..params()
+            }
+            .validate(),
+            Err(StdError::generic_err("owner key is required"))
+        );
+        assert_eq!(
+            EOLParams {
+                owner_key: Some(BeneficiaryKey::Ed25519(Binary::from(vec![1, 2, 3]))),
+                ..params()
+            }
+            .validate(),
+            Err(StdError::generic_err("invalid owner public key: AQID"))
+        );
+        assert_eq!(
+            EOLParams {
+                owner_key: Some(BeneficiaryKey::Ed25519(hex(ED25519_PUBKEY))),
+                ..params()
+            }
+            .validate(),
+            Err(StdError::generic_err(
+                "owner key must not be a beneficiary key"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_params_without_beneficiaries() {
+        let params: EOLParams =
+            cosmwasm_std::from_json(r#"{"inactivity_period":"100000000000"}"#).unwrap();
+        assert!(params.beneficiaries.is_empty());
+        assert!(params.beneficiary_commitments.is_empty());
+    }
+
+    #[test]
+    fn test_params_inactivity_period() {
+        // params added while the period was a `Timestamp` keep their meaning
+        let legacy: EOLParams =
+            cosmwasm_std::from_json(r#"{"inactivity_period":"31536000000000000"}"#).unwrap();
+        let human: EOLParams = cosmwasm_std::from_json(r#"{"inactivity_period":"365d"}"#).unwrap();
+        let seconds: EOLParams =
+            cosmwasm_std::from_json(r#"{"inactivity_period":31536000}"#).unwrap();
+
+        assert_eq!(
+            legacy.inactivity_period,
+            Duration::from_seconds(31_536_000).unwrap()
+        );
+        assert_eq!(human.inactivity_period, legacy.inactivity_period);
+        assert_eq!(seconds.inactivity_period, legacy.inactivity_period);
+    }
+
+    #[test]
+    fn test_params_budget_periods() {
+        // so do the periods of fee budgets and withdrawal caps
+        let legacy: EOLParams = cosmwasm_std::from_json(
+            r#"{
+                "inactivity_period": "1d",
+                "fee_budget": {"limit": [], "period": "604800000000000"},
+                "withdrawal_cap": {"limit": "100", "period": "604800000000000"}
+            }"#,
+        )
+        .unwrap();
+
+        let week = Duration::from_seconds(604_800).unwrap();
+        assert_eq!(legacy.fee_budget.unwrap().period, week);
+        assert_eq!(legacy.withdrawal_cap.unwrap().period, week);
+    }
+}
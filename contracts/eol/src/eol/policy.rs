@@ -0,0 +1,387 @@
+use cosmwasm_std::Coin;
+use cw_authenticator::Any;
+use osmosis_std::types::cosmwasm::wasm::v1::MsgExecuteContract;
+
+use super::asset::{AssetMsg, AssetTransfer};
+use super::error::{EOLError, EOLResult};
+use super::params::EOLParams;
+use super::transfer::Transfer;
+use super::unpack::unpack;
+use super::wasm::instantiation;
+use super::withdrawal::outflows;
+
+/// Type url prefix shared by all messages of the smart account module
+const SMART_ACCOUNT_MSG_PREFIX: &str = "/osmosis.smartaccount.";
+
+/// Classes of messages the beneficiary policy reasons about
+#[derive(Debug, PartialEq)]
+pub enum MsgClass {
+    /// Adds, removes or toggles the account's authenticators
+    AuthenticatorManagement,
+    Other,
+}
+
+impl MsgClass {
+    pub fn of(type_url: &str) -> Self {
+        if type_url.starts_with(SMART_ACCOUNT_MSG_PREFIX) {
+            MsgClass::AuthenticatorManagement
+        } else {
+            MsgClass::Other
+        }
+    }
+}
+
+impl EOLParams {
+    /// Ensure `msg`, and every message wrapped in it, may be sent
+    /// by a beneficiary on behalf of the dormant account
+    pub fn ensure_allowed_for_beneficiary(&self, msg: &Any) -> EOLResult<()> {
+        unpack(msg)?
+            .iter()
+            .try_for_each(|msg| self.ensure_msg_allowed(msg))
+    }
+
+    /// Whether outflows of some denoms are restricted
+    pub fn restricts_denoms(&self) -> bool {
+        self.allowed_denoms.is_some() || !self.denied_denoms.is_empty()
+    }
+
+    pub fn is_denom_allowed(&self, denom: &str) -> bool {
+        let allowed = self
+            .allowed_denoms
+            .as_ref()
+            .is_none_or(|allowed_denoms| allowed_denoms.iter().any(|d| d == denom));
+
+        allowed && !self.denied_denoms.iter().any(|d| d == denom)
+    }
+
+    /// Ensure no restricted denom left the account between the `before` and `after` balances,
+    /// whichever message moved it
+    pub fn ensure_denoms_kept(&self, before: &[Coin], after: &[Coin]) -> EOLResult<()> {
+        match outflows(before, after)
+            .into_iter()
+            .find(|coin| !self.is_denom_allowed(&coin.denom))
+        {
+            Some(coin) => Err(EOLError::denom_not_allowed(&coin.denom)),
+            None => Ok(()),
+        }
+    }
+
+    fn ensure_msg_allowed(&self, msg: &Any) -> EOLResult<()> {
+        if MsgClass::of(&msg.type_url) == MsgClass::AuthenticatorManagement
+            && !self.allow_authenticator_management
+        {
+            return Err(EOLError::message_not_allowed(&msg.type_url));
+        }
+
+        if let Some(allowed_destinations) = &self.allowed_destinations {
+            if let Some(recipient) = destinations(msg)?
+                .into_iter()
+                .find(|recipient| !allowed_destinations.contains(recipient))
+            {
+                return Err(EOLError::destination_not_allowed(&recipient));
+            }
+        }
+
+        self.ensure_ibc_transfer_allowed(msg)?;
+
+        if let Some(asset_msg) = AssetMsg::decode(msg)? {
+            self.ensure_asset_msg_allowed(&asset_msg)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Addresses `msg` hands funds or tokens to: recipients of bank and IBC transfers,
+/// contracts executed with funds and recipients of cw20 and cw721 tokens
+fn destinations(msg: &Any) -> EOLResult<Vec<String>> {
+    if let Some(transfer) = Transfer::decode(msg)? {
+        return Ok(transfer.recipients);
+    }
+
+    // the address of the instantiated contract isn't known upfront
+    if let Some((code_id, true)) = instantiation(msg)? {
+        return Err(EOLError::FundedInstantiationNotAllowed { code_id });
+    }
+
+    let mut destinations = vec![];
+    if msg.type_url == MsgExecuteContract::TYPE_URL {
+        let execute = MsgExecuteContract::try_from(msg.value.clone())
+            .map_err(|_| EOLError::undecodable_message(&msg.type_url))?;
+        if !execute.funds.is_empty() {
+            destinations.push(execute.contract);
+        }
+    }
+
+    match AssetMsg::decode(msg)? {
+        Some(AssetMsg::Transfer(AssetTransfer {
+            recipient: Some(recipient),
+            ..
+        })) => destinations.push(recipient),
+        // allowances let the spender move the tokens anywhere
+        Some(AssetMsg::Unsupported { contract, .. }) => {
+            return Err(EOLError::asset_msg_not_allowed(&contract))
+        }
+        _ => {}
+    }
+
+    Ok(destinations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{coin, coins, Binary};
+    use osmosis_std::types::cosmos::bank::v1beta1::{MsgMultiSend, MsgSend};
+    use osmosis_std::types::cosmwasm::wasm::v1::MsgInstantiateContract;
+    use osmosis_std::types::ibc::applications::transfer::v1::MsgTransfer;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::add_authenticator("/osmosis.smartaccount.v1beta1.MsgAddAuthenticator", false, false)]
+    #[case::add_authenticator_opt_in(
+        "/osmosis.smartaccount.v1beta1.MsgAddAuthenticator",
+        true,
+        true
+    )]
+    #[case::remove_authenticator(
+        "/osmosis.smartaccount.v1beta1.MsgRemoveAuthenticator",
+        false,
+        false
+    )]
+    #[case::set_active_state("/osmosis.smartaccount.v1beta1.MsgSetActiveState", false, false)]
+    #[case::bank_send("/cosmos.bank.v1beta1.MsgSend", false, true)]
+    fn test_authenticator_management(
+        #[case] type_url: &str,
+        #[case] allow_authenticator_management: bool,
+        #[case] allowed: bool,
+    ) {
+        let params = EOLParams {
+            allow_authenticator_management,
+            ..EOLParams::default()
+        };
+        let msg = Any {
+            type_url: type_url.to_string(),
+            value: Binary::default(),
+        };
+
+        let result = params.ensure_allowed_for_beneficiary(&msg);
+        if allowed {
+            assert_eq!(result, Ok(()));
+        } else {
+            assert_eq!(result, Err(EOLError::message_not_allowed(type_url)));
+        }
+    }
+
+    #[test]
+    fn test_wrapped_authenticator_management() {
+        let remove_authenticator = osmosis_std::shim::Any {
+            type_url: "/osmosis.smartaccount.v1beta1.MsgRemoveAuthenticator".to_string(),
+            value: vec![],
+        };
+        let msg = Any {
+            type_url: "/cosmos.authz.v1beta1.MsgExec".to_string(),
+            value: osmosis_std::types::cosmos::authz::v1beta1::MsgExec {
+                grantee: "account".to_string(),
+                msgs: vec![remove_authenticator],
+            }
+            .into(),
+        };
+
+        assert_eq!(
+            EOLParams::default().ensure_allowed_for_beneficiary(&msg),
+            Err(EOLError::message_not_allowed(
+                "/osmosis.smartaccount.v1beta1.MsgRemoveAuthenticator"
+            ))
+        );
+    }
+
+    fn send(to_address: &str) -> Any {
+        Any {
+            type_url: MsgSend::TYPE_URL.to_string(),
+            value: MsgSend {
+                from_address: "account".to_string(),
+                to_address: to_address.to_string(),
+                amount: vec![],
+            }
+            .into(),
+        }
+    }
+
+    fn multi_send(addresses: &[&str]) -> Any {
+        Any {
+            type_url: MsgMultiSend::TYPE_URL.to_string(),
+            value: MsgMultiSend {
+                inputs: vec![],
+                outputs: addresses
+                    .iter()
+                    .map(
+                        |address| osmosis_std::types::cosmos::bank::v1beta1::Output {
+                            address: address.to_string(),
+                            coins: vec![],
+                        },
+                    )
+                    .collect(),
+            }
+            .into(),
+        }
+    }
+
+    fn ibc_transfer(receiver: &str) -> Any {
+        Any {
+            type_url: MsgTransfer::TYPE_URL.to_string(),
+            value: MsgTransfer {
+                source_port: "transfer".to_string(),
+                source_channel: "channel-0".to_string(),
+                sender: "account".to_string(),
+                receiver: receiver.to_string(),
+                ..MsgTransfer::default()
+            }
+            .into(),
+        }
+    }
+
+    fn execute(contract: &str, msg: &str, funds: Vec<Coin>) -> Any {
+        Any {
+            type_url: MsgExecuteContract::TYPE_URL.to_string(),
+            value: MsgExecuteContract {
+                sender: "account".to_string(),
+                contract: contract.to_string(),
+                msg: msg.as_bytes().to_vec(),
+                funds: funds.into_iter().map(Into::into).collect(),
+            }
+            .into(),
+        }
+    }
+
+    fn instantiate(funds: Vec<Coin>) -> Any {
+        Any {
+            type_url: MsgInstantiateContract::TYPE_URL.to_string(),
+            value: MsgInstantiateContract {
+                sender: "account".to_string(),
+                code_id: 7,
+                msg: b"{}".to_vec(),
+                funds: funds.into_iter().map(Into::into).collect(),
+                ..MsgInstantiateContract::default()
+            }
+            .into(),
+        }
+    }
+
+    #[rstest]
+    #[case::send(send("cold_wallet"), None, Ok(()))]
+    #[case::send_unrestricted(send("phisher"), None, Ok(()))]
+    #[case::send_unlisted(send("phisher"), Some(vec![]), Err(EOLError::destination_not_allowed("phisher")))]
+    #[case::send_listed(send("cold_wallet"), Some(vec!["cold_wallet"]), Ok(()))]
+    #[case::multi_send_listed(multi_send(&["cold_wallet", "other_wallet"]), Some(vec!["cold_wallet", "other_wallet"]), Ok(()))]
+    #[case::multi_send_unlisted(
+        multi_send(&["cold_wallet", "phisher"]),
+        Some(vec!["cold_wallet"]),
+        Err(EOLError::destination_not_allowed("phisher"))
+    )]
+    #[case::ibc_transfer_listed(ibc_transfer("cosmos1cold"), Some(vec!["cosmos1cold"]), Ok(()))]
+    #[case::ibc_transfer_unlisted(
+        ibc_transfer("cosmos1phisher"),
+        Some(vec!["cosmos1cold"]),
+        Err(EOLError::destination_not_allowed("cosmos1phisher"))
+    )]
+    #[case::funded_execute_listed(
+        execute("vault", r#"{"deposit":{}}"#, coins(1, "uosmo")),
+        Some(vec!["vault"]),
+        Ok(())
+    )]
+    #[case::funded_execute_unlisted(
+        execute("forwarder", r#"{"forward":{}}"#, coins(1, "uosmo")),
+        Some(vec!["vault"]),
+        Err(EOLError::destination_not_allowed("forwarder"))
+    )]
+    #[case::unfunded_execute_unlisted(
+        execute("pool", r#"{"swap":{}}"#, vec![]),
+        Some(vec!["vault"]),
+        Ok(())
+    )]
+    #[case::funded_instantiate(
+        instantiate(coins(1, "uosmo")),
+        Some(vec!["vault"]),
+        Err(EOLError::FundedInstantiationNotAllowed { code_id: 7 })
+    )]
+    #[case::unfunded_instantiate(instantiate(vec![]), Some(vec!["vault"]), Ok(()))]
+    #[case::funded_instantiate_unrestricted(instantiate(coins(1, "uosmo")), None, Ok(()))]
+    #[case::cw20_transfer_listed(
+        execute("token", r#"{"transfer":{"recipient":"cold_wallet","amount":"1"}}"#, vec![]),
+        Some(vec!["cold_wallet"]),
+        Ok(())
+    )]
+    #[case::cw20_transfer_unlisted(
+        execute("token", r#"{"transfer":{"recipient":"phisher","amount":"1"}}"#, vec![]),
+        Some(vec!["cold_wallet"]),
+        Err(EOLError::destination_not_allowed("phisher"))
+    )]
+    #[case::cw20_send_unlisted(
+        execute("token", r#"{"send":{"contract":"forwarder","amount":"1","msg":""}}"#, vec![]),
+        Some(vec!["cold_wallet"]),
+        Err(EOLError::destination_not_allowed("forwarder"))
+    )]
+    #[case::cw721_transfer_unlisted(
+        execute("nft", r#"{"transfer_nft":{"recipient":"phisher","token_id":"1"}}"#, vec![]),
+        Some(vec!["cold_wallet"]),
+        Err(EOLError::destination_not_allowed("phisher"))
+    )]
+    #[case::cw20_burn(
+        execute("token", r#"{"burn":{"amount":"1"}}"#, vec![]),
+        Some(vec!["cold_wallet"]),
+        Ok(())
+    )]
+    #[case::cw20_allowance(
+        execute("token", r#"{"increase_allowance":{"spender":"phisher","amount":"1"}}"#, vec![]),
+        Some(vec!["cold_wallet"]),
+        Err(EOLError::asset_msg_not_allowed("token"))
+    )]
+    fn test_allowed_destinations(
+        #[case] msg: Any,
+        #[case] allowed_destinations: Option<Vec<&str>>,
+        #[case] expected: EOLResult<()>,
+    ) {
+        let params = EOLParams {
+            allowed_destinations: allowed_destinations
+                .map(|destinations| destinations.into_iter().map(String::from).collect()),
+            ..EOLParams::default()
+        };
+
+        assert_eq!(params.ensure_allowed_for_beneficiary(&msg), expected);
+    }
+
+    #[rstest]
+    #[case::unrestricted(None, vec![], coins(90, "ugov"), Ok(()))]
+    #[case::allowed(Some(vec!["uosmo"]), vec![], vec![coin(100, "ugov"), coin(10, "uosmo")], Ok(()))]
+    #[case::not_allowed(
+        Some(vec!["uosmo"]),
+        vec![],
+        vec![coin(90, "ugov"), coin(100, "uosmo")],
+        Err(EOLError::denom_not_allowed("ugov"))
+    )]
+    #[case::denied(None, vec!["ugov"], coins(100, "uosmo"), Err(EOLError::denom_not_allowed("ugov")))]
+    #[case::denied_over_allowed(
+        Some(vec!["ugov", "uosmo"]),
+        vec!["ugov"],
+        vec![coin(0, "ugov"), coin(100, "uosmo")],
+        Err(EOLError::denom_not_allowed("ugov"))
+    )]
+    #[case::received(None, vec!["ugov"], vec![coin(200, "ugov"), coin(100, "uosmo")], Ok(()))]
+    fn test_ensure_denoms_kept(
+        #[case] allowed_denoms: Option<Vec<&str>>,
+        #[case] denied_denoms: Vec<&str>,
+        #[case] after: Vec<Coin>,
+        #[case] expected: EOLResult<()>,
+    ) {
+        let params = EOLParams {
+            allowed_denoms: allowed_denoms
+                .map(|denoms| denoms.into_iter().map(String::from).collect()),
+            denied_denoms: denied_denoms.into_iter().map(String::from).collect(),
+            ..EOLParams::default()
+        };
+        let before = vec![coin(100, "ugov"), coin(100, "uosmo")];
+
+        assert_eq!(params.ensure_denoms_kept(&before, &after), expected);
+    }
+}
@@ -0,0 +1,147 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ensure, Addr, Coin, Decimal, QuerierWrapper, StdError, StdResult, Uint128};
+
+use super::error::{EOLError, EOLResult};
+
+/// Balance that must stay in the account whatever beneficiaries do with it
+#[cw_serde]
+pub enum Reserve {
+    /// Fixed amount of a denom
+    Amount(Coin),
+    /// Share of the balance the account held when beneficiaries first used it
+    Ratio { denom: String, ratio: Decimal },
+}
+
+impl Reserve {
+    pub fn validate(&self) -> StdResult<()> {
+        if let Reserve::Ratio { denom, ratio } = self {
+            ensure!(
+                *ratio <= Decimal::one(),
+                StdError::generic_err(format!("reserve ratio of {denom} exceeds 1: {ratio}"))
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn denom(&self) -> &str {
+        match self {
+            Reserve::Amount(coin) => &coin.denom,
+            Reserve::Ratio { denom, .. } => denom,
+        }
+    }
+
+    /// Amount held back given the snapshotted `balance` of the denom
+    pub fn amount(&self, balance: Uint128) -> Uint128 {
+        match self {
+            Reserve::Amount(coin) => coin.amount,
+            Reserve::Ratio { ratio, .. } => balance.mul_ceil(*ratio),
+        }
+    }
+}
+
+/// Snapshot the balances of `account` and resolve them into the amounts to hold back,
+/// the highest reserve wins when a denom is reserved more than once
+pub fn resolve_reserve(
+    querier: &QuerierWrapper,
+    account: &Addr,
+    reserve: &[Reserve],
+) -> StdResult<Vec<Coin>> {
+    let mut resolved: Vec<Coin> = vec![];
+    for r in reserve {
+        let balance = querier.query_balance(account, r.denom())?.amount;
+        let amount = r.amount(balance);
+
+        match resolved.iter_mut().find(|coin| coin.denom == r.denom()) {
+            Some(coin) => coin.amount = coin.amount.max(amount),
+            None => resolved.push(Coin::new(amount.u128(), r.denom())),
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Ensure the balances of `account` did not drop below the `reserved` amounts
+pub fn ensure_reserve_kept(
+    querier: &QuerierWrapper,
+    account: &Addr,
+    reserved: &[Coin],
+) -> EOLResult<()> {
+    for reserve in reserved {
+        let balance = querier.query_balance(account, &reserve.denom)?.amount;
+        ensure!(
+            balance >= reserve.amount,
+            EOLError::ReserveBreached {
+                denom: reserve.denom.clone(),
+                reserve: reserve.amount,
+                balance,
+            }
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{coin, testing::mock_dependencies_with_balances};
+
+    #[test]
+    fn test_resolve_reserve() {
+        let deps = mock_dependencies_with_balances(&[(
+            "account",
+            &[coin(1_005, "uosmo"), coin(300, "uion")],
+        )]);
+        let querier = QuerierWrapper::new(&deps.querier);
+        let account = Addr::unchecked("account");
+
+        let reserve = vec![
+            Reserve::Ratio {
+                denom: "uosmo".to_string(),
+                ratio: Decimal::percent(10),
+            },
+            Reserve::Amount(coin(50, "uosmo")),
+            Reserve::Amount(coin(500, "uion")),
+            Reserve::Ratio {
+                denom: "uatom".to_string(),
+                ratio: Decimal::percent(10),
+            },
+        ];
+
+        assert_eq!(
+            resolve_reserve(&querier, &account, &reserve).unwrap(),
+            vec![coin(101, "uosmo"), coin(500, "uion"), coin(0, "uatom")]
+        );
+    }
+
+    #[test]
+    fn test_ensure_reserve_kept() {
+        let deps = mock_dependencies_with_balances(&[("account", &[coin(100, "uosmo")])]);
+        let querier = QuerierWrapper::new(&deps.querier);
+        let account = Addr::unchecked("account");
+
+        assert_eq!(
+            ensure_reserve_kept(&querier, &account, &[coin(100, "uosmo")]),
+            Ok(())
+        );
+        assert_eq!(
+            ensure_reserve_kept(&querier, &account, &[coin(101, "uosmo")]),
+            Err(EOLError::ReserveBreached {
+                denom: "uosmo".to_string(),
+                reserve: Uint128::new(101),
+                balance: Uint128::new(100),
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Reserve::Ratio {
+            denom: "uosmo".to_string(),
+            ratio: Decimal::percent(101),
+        }
+        .validate()
+        .is_err());
+    }
+}
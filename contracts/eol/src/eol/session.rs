@@ -0,0 +1,89 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, BlockInfo, Coin};
+
+/// Who authenticated a message of the in-flight transaction
+#[cw_serde]
+pub enum Signer {
+    Owner,
+    /// Beneficiary holding the public key at `index` of `EOLParams::beneficiaries`
+    Beneficiary {
+        index: u32,
+    },
+    /// Beneficiary committed to in `EOLParams::beneficiary_commitments`
+    CommittedBeneficiary {
+        address: Addr,
+    },
+}
+
+impl Signer {
+    /// Identifies the signer across transactions
+    pub fn id(&self) -> String {
+        match self {
+            Signer::Owner => "owner".to_string(),
+            Signer::Beneficiary { index } => format!("beneficiary/{index}"),
+            Signer::CommittedBeneficiary { address } => address.to_string(),
+        }
+    }
+}
+
+/// Authentication outcome of a message, recorded in `authenticate`
+/// and consumed by `track` and `confirm_execution` of the same message.
+#[cw_serde]
+pub struct Session {
+    pub signer: Signer,
+
+    /// Block height the session was recorded at.
+    /// A failed transaction never reaches `confirm_execution`, so sessions
+    /// from previous blocks are stale and must be ignored.
+    pub height: u64,
+
+    /// Whether the message is part of a beneficiary's takeover of the account
+    #[serde(default)]
+    pub takeover: bool,
+
+    /// Whether the transaction is only being simulated, in which case
+    /// the hooks run their checks but leave the EOL state untouched
+    #[serde(default)]
+    pub simulate: bool,
+
+    /// Balances that must be left in the account once the message executed,
+    /// resolved in `track`
+    #[serde(default)]
+    pub reserved: Vec<Coin>,
+
+    /// Balances of the account before the transaction executed,
+    /// snapshotted in `track` when outflows are restricted or capped
+    #[serde(default)]
+    pub balances: Vec<Coin>,
+}
+
+impl Session {
+    pub fn new(signer: Signer, block: &BlockInfo) -> Self {
+        Self {
+            signer,
+            height: block.height,
+            takeover: false,
+            simulate: false,
+            reserved: vec![],
+            balances: vec![],
+        }
+    }
+
+    pub fn with_takeover(mut self, takeover: bool) -> Self {
+        self.takeover = takeover;
+        self
+    }
+
+    pub fn with_simulate(mut self, simulate: bool) -> Self {
+        self.simulate = simulate;
+        self
+    }
+
+    pub fn is_current(&self, block: &BlockInfo) -> bool {
+        self.height == block.height
+    }
+
+    pub fn is_beneficiary(&self) -> bool {
+        !matches!(self.signer, Signer::Owner)
+    }
+}
@@ -0,0 +1,240 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ensure, Addr, Coin, Coins, QuerierWrapper, StdResult};
+use cw_authenticator::Any;
+use osmosis_std::types::cosmos::base::query::v1beta1::PageRequest;
+use osmosis_std::types::cosmos::distribution::v1beta1::MsgWithdrawDelegatorReward;
+use osmosis_std::types::cosmos::staking::v1beta1::{
+    MsgBeginRedelegate, MsgUndelegate, StakingQuerier,
+};
+
+use super::error::{EOLError, EOLResult};
+use super::params::EOLParams;
+use super::unpack::unpack;
+
+/// Type url prefixes of the staking and distribution modules
+const STAKING_MSG_PREFIXES: [&str; 2] = ["/cosmos.staking.", "/cosmos.distribution."];
+
+/// Staking messages that wind the account's stake down
+const UNWINDING_MSGS: [&str; 3] = [
+    MsgWithdrawDelegatorReward::TYPE_URL,
+    MsgUndelegate::TYPE_URL,
+    MsgBeginRedelegate::TYPE_URL,
+];
+
+/// Policy for inheriting a staking account: beneficiaries may claim rewards, undelegate and
+/// redelegate right away, while what leaves the account is capped until all stake is unbonded
+#[cw_serde]
+pub struct StakingPreset {
+    /// Most of each denom that may leave the account in total, by any message, while it still
+    /// has stake delegated or unbonding. Denoms not listed can't leave until then.
+    pub transfer_cap: Vec<Coin>,
+}
+
+impl StakingPreset {
+    /// Ensure `msg` does not add to the stake
+    pub fn ensure_msg_allowed(&self, msg: &Any) -> EOLResult<()> {
+        let type_url = msg.type_url.as_str();
+        ensure!(
+            !STAKING_MSG_PREFIXES
+                .iter()
+                .any(|prefix| type_url.starts_with(prefix))
+                || UNWINDING_MSGS.contains(&type_url),
+            EOLError::message_not_allowed(type_url)
+        );
+
+        Ok(())
+    }
+
+    /// Add `outflows` to the `total` that left the account while staked,
+    /// ensuring it stays within the cap
+    pub fn record_outflows(&self, total: &mut Vec<Coin>, outflows: &[Coin]) -> EOLResult<()> {
+        let mut moved = Coins::default();
+        for coin in total.iter().chain(outflows) {
+            moved.add(coin.clone())?;
+        }
+
+        for coin in moved.iter() {
+            let cap = self
+                .transfer_cap
+                .iter()
+                .find(|cap| cap.denom == coin.denom)
+                .map(|cap| cap.amount)
+                .unwrap_or_default();
+
+            ensure!(
+                coin.amount <= cap,
+                EOLError::TransferCapExceeded {
+                    denom: coin.denom.clone(),
+                    cap,
+                }
+            );
+        }
+
+        *total = moved.into_vec();
+        Ok(())
+    }
+}
+
+/// Whether `account` still has stake delegated or unbonding
+pub fn is_staked(querier: &QuerierWrapper, account: &Addr) -> StdResult<bool> {
+    let staking = StakingQuerier::new(querier);
+    let first = || {
+        Some(PageRequest {
+            limit: 1,
+            ..PageRequest::default()
+        })
+    };
+
+    let delegated = !staking
+        .delegator_delegations(account.to_string(), first())?
+        .delegation_responses
+        .is_empty();
+
+    Ok(delegated
+        || !staking
+            .delegator_unbonding_delegations(account.to_string(), first())?
+            .unbonding_responses
+            .is_empty())
+}
+
+impl EOLParams {
+    /// Ensure `msg`, and every message wrapped in it, complies with the staking preset if any.
+    /// The transfer cap is enforced against the balances once executed.
+    pub fn ensure_staking_allowed(&self, msg: &Any) -> EOLResult<()> {
+        let Some(staking) = &self.staking else {
+            return Ok(());
+        };
+
+        unpack(msg)?
+            .iter()
+            .try_for_each(|msg| staking.ensure_msg_allowed(msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helper::mock_stargate_querier::{
+        delegator_staking_query_handler, mock_dependencies_with_stargate_querier,
+    };
+    use cosmwasm_std::{coin, coins, Binary, Uint128};
+    use osmosis_std::types::cosmos::bank::v1beta1::MsgSend;
+    use osmosis_std::types::cosmos::staking::v1beta1::MsgDelegate;
+    use rstest::rstest;
+
+    fn send(amount: Vec<Coin>) -> Any {
+        Any {
+            type_url: MsgSend::TYPE_URL.to_string(),
+            value: MsgSend {
+                from_address: "account".to_string(),
+                to_address: "heir".to_string(),
+                amount: amount.into_iter().map(Into::into).collect(),
+            }
+            .into(),
+        }
+    }
+
+    fn preset() -> StakingPreset {
+        StakingPreset {
+            transfer_cap: coins(100, "uosmo"),
+        }
+    }
+
+    #[rstest]
+    #[case::withdraw_rewards(MsgWithdrawDelegatorReward::TYPE_URL, true)]
+    #[case::undelegate(MsgUndelegate::TYPE_URL, true)]
+    #[case::redelegate(MsgBeginRedelegate::TYPE_URL, true)]
+    #[case::delegate(MsgDelegate::TYPE_URL, false)]
+    #[case::cancel_unbonding("/cosmos.staking.v1beta1.MsgCancelUnbondingDelegation", false)]
+    #[case::set_withdraw_address("/cosmos.distribution.v1beta1.MsgSetWithdrawAddress", false)]
+    fn test_staking_msgs(#[case] type_url: &str, #[case] allowed: bool) {
+        let msg = Any {
+            type_url: type_url.to_string(),
+            value: Binary::default(),
+        };
+
+        let result = preset().ensure_msg_allowed(&msg);
+        if allowed {
+            assert_eq!(result, Ok(()));
+        } else {
+            assert_eq!(result, Err(EOLError::message_not_allowed(type_url)));
+        }
+    }
+
+    #[test]
+    fn test_record_outflows() {
+        let preset = preset();
+        let mut total = vec![];
+
+        preset
+            .record_outflows(&mut total, &coins(60, "uosmo"))
+            .unwrap();
+        assert_eq!(total, coins(60, "uosmo"));
+
+        // each under the cap, together over it
+        assert_eq!(
+            preset.record_outflows(&mut total, &coins(41, "uosmo")),
+            Err(EOLError::TransferCapExceeded {
+                denom: "uosmo".to_string(),
+                cap: Uint128::new(100),
+            })
+        );
+        assert_eq!(total, coins(60, "uosmo"));
+
+        preset
+            .record_outflows(&mut total, &coins(40, "uosmo"))
+            .unwrap();
+        assert_eq!(total, coins(100, "uosmo"));
+
+        // denoms not listed can't leave at all
+        assert_eq!(
+            preset.record_outflows(&mut total, &[coin(1, "uion")]),
+            Err(EOLError::TransferCapExceeded {
+                denom: "uion".to_string(),
+                cap: Uint128::zero(),
+            })
+        );
+    }
+
+    #[rstest]
+    #[case::delegated(true, false, true)]
+    #[case::unbonding(false, true, true)]
+    #[case::unbonded(false, false, false)]
+    fn test_is_staked(#[case] delegated: bool, #[case] unbonding: bool, #[case] staked: bool) {
+        let deps = mock_dependencies_with_stargate_querier(
+            &[],
+            delegator_staking_query_handler(delegated, unbonding),
+        );
+        let querier = QuerierWrapper::new(&deps.querier);
+
+        assert_eq!(is_staked(&querier, &Addr::unchecked("account")), Ok(staked));
+    }
+
+    #[test]
+    fn test_ensure_staking_allowed() {
+        let params = EOLParams {
+            staking: Some(preset()),
+            ..EOLParams::default()
+        };
+        let delegate = Any {
+            type_url: MsgDelegate::TYPE_URL.to_string(),
+            value: Binary::default(),
+        };
+
+        // transfers are capped once executed
+        assert_eq!(
+            params.ensure_staking_allowed(&send(coins(1_000, "uosmo"))),
+            Ok(())
+        );
+        assert_eq!(
+            params.ensure_staking_allowed(&delegate),
+            Err(EOLError::message_not_allowed(MsgDelegate::TYPE_URL))
+        );
+
+        // no restriction without the preset
+        assert_eq!(
+            EOLParams::default().ensure_staking_allowed(&delegate),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use cosmwasm_std::Addr;
+use cw_authenticator::Any;
+use osmosis_std::types::osmosis::smartaccount::v1beta1::{
+    AccountAuthenticator, MsgAddAuthenticator, MsgRemoveAuthenticator,
+};
+
+use super::error::{EOLError, EOLResult};
+
+/// Authenticator type the beneficiary's key gets registered as
+pub const SIGNATURE_VERIFICATION: &str = "SignatureVerification";
+
+/// Ensure `msgs` is exactly a takeover of `account`:
+/// adding `pubkey` as signature verification authenticator, followed by
+/// the removal of every `existing` authenticator but the one rooted at `keep`,
+/// which is the authenticator that tracks the claim.
+pub fn ensure_takeover_shape(
+    msgs: &[Any],
+    account: &Addr,
+    pubkey: &[u8],
+    existing: &[u64],
+    keep: u64,
+) -> EOLResult<()> {
+    let Some((add, removals)) = msgs.split_first() else {
+        return Err(EOLError::invalid_takeover("empty transaction"));
+    };
+
+    ensure_takeover_msg(0, add, account, pubkey)?;
+
+    let mut removed = removals
+        .iter()
+        .enumerate()
+        .map(|(i, msg)| {
+            ensure_takeover_msg(i + 1, msg, account, pubkey)?;
+            Ok(MsgRemoveAuthenticator::try_from(msg.value.clone())?.id)
+        })
+        .collect::<EOLResult<Vec<_>>>()?;
+    removed.sort_unstable();
+
+    let mut expected = existing
+        .iter()
+        .copied()
+        .filter(|id| *id != keep)
+        .collect::<Vec<_>>();
+    expected.sort_unstable();
+
+    if removed != expected {
+        return Err(EOLError::invalid_takeover(
+            "must remove every other authenticator of the account exactly once",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Ensure the message at `msg_index` of a takeover transaction has the expected shape
+pub fn ensure_takeover_msg(
+    msg_index: usize,
+    msg: &Any,
+    account: &Addr,
+    pubkey: &[u8],
+) -> EOLResult<()> {
+    if msg_index == 0 {
+        if msg.type_url != MsgAddAuthenticator::TYPE_URL {
+            return Err(EOLError::invalid_takeover(
+                "first message must add the beneficiary's authenticator",
+            ));
+        }
+
+        let add = MsgAddAuthenticator::try_from(msg.value.clone())?;
+        if add.sender != account.as_str()
+            || add.r#type != SIGNATURE_VERIFICATION
+            || add.data != pubkey
+        {
+            return Err(EOLError::invalid_takeover(
+                "must add the beneficiary's key as signature verification",
+            ));
+        }
+    } else {
+        if msg.type_url != MsgRemoveAuthenticator::TYPE_URL {
+            return Err(EOLError::invalid_takeover(
+                "remaining messages must remove authenticators",
+            ));
+        }
+
+        let remove = MsgRemoveAuthenticator::try_from(msg.value.clone())?;
+        if remove.sender != account.as_str() {
+            return Err(EOLError::invalid_takeover(
+                "must remove the account's own authenticators",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Ensure that, after execution, the account is only left with
+/// the authenticator rooted at `keep` and the beneficiary's key
+pub fn ensure_taken_over(
+    authenticators: &[AccountAuthenticator],
+    pubkey: &[u8],
+    keep: u64,
+) -> EOLResult<()> {
+    let mut others = authenticators.iter().filter(|a| a.id != keep);
+
+    match (others.next(), others.next()) {
+        (Some(a), None) if a.r#type == SIGNATURE_VERIFICATION && a.config == pubkey => Ok(()),
+        _ => Err(EOLError::invalid_takeover(
+            "account must only be controlled by the beneficiary's key",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const PUBKEY: &[u8] = &[2; 33];
+
+    fn add(sender: &str, r#type: &str, data: &[u8]) -> Any {
+        Any {
+            type_url: MsgAddAuthenticator::TYPE_URL.to_string(),
+            value: MsgAddAuthenticator {
+                sender: sender.to_string(),
+                r#type: r#type.to_string(),
+                data: data.to_vec(),
+            }
+            .into(),
+        }
+    }
+
+    fn remove(sender: &str, id: u64) -> Any {
+        Any {
+            type_url: MsgRemoveAuthenticator::TYPE_URL.to_string(),
+            value: MsgRemoveAuthenticator {
+                sender: sender.to_string(),
+                id,
+            }
+            .into(),
+        }
+    }
+
+    #[rstest]
+    #[case::takeover(
+        vec![add("account", SIGNATURE_VERIFICATION, PUBKEY), remove("account", 3), remove("account", 1)],
+        Ok(())
+    )]
+    #[case::empty(vec![], Err(EOLError::invalid_takeover("empty transaction")))]
+    #[case::removal_first(
+        vec![remove("account", 1), add("account", SIGNATURE_VERIFICATION, PUBKEY), remove("account", 3)],
+        Err(EOLError::invalid_takeover("first message must add the beneficiary's authenticator"))
+    )]
+    #[case::other_key(
+        vec![add("account", SIGNATURE_VERIFICATION, &[3; 33]), remove("account", 1), remove("account", 3)],
+        Err(EOLError::invalid_takeover("must add the beneficiary's key as signature verification"))
+    )]
+    #[case::other_type(
+        vec![add("account", "AllOf", PUBKEY), remove("account", 1), remove("account", 3)],
+        Err(EOLError::invalid_takeover("must add the beneficiary's key as signature verification"))
+    )]
+    #[case::other_account(
+        vec![add("account", SIGNATURE_VERIFICATION, PUBKEY), remove("other", 1), remove("account", 3)],
+        Err(EOLError::invalid_takeover("must remove the account's own authenticators"))
+    )]
+    #[case::owner_authenticator_left(
+        vec![add("account", SIGNATURE_VERIFICATION, PUBKEY), remove("account", 1)],
+        Err(EOLError::invalid_takeover("must remove every other authenticator of the account exactly once"))
+    )]
+    #[case::removes_eol_authenticator(
+        vec![add("account", SIGNATURE_VERIFICATION, PUBKEY), remove("account", 1), remove("account", 2), remove("account", 3)],
+        Err(EOLError::invalid_takeover("must remove every other authenticator of the account exactly once"))
+    )]
+    #[case::duplicated_removal(
+        vec![add("account", SIGNATURE_VERIFICATION, PUBKEY), remove("account", 1), remove("account", 1), remove("account", 3)],
+        Err(EOLError::invalid_takeover("must remove every other authenticator of the account exactly once"))
+    )]
+    fn test_ensure_takeover_shape(#[case] msgs: Vec<Any>, #[case] expected: EOLResult<()>) {
+        assert_eq!(
+            ensure_takeover_shape(&msgs, &Addr::unchecked("account"), PUBKEY, &[1, 2, 3], 2),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_ensure_taken_over() {
+        let authenticator = |id: u64, r#type: &str, config: &[u8]| AccountAuthenticator {
+            id,
+            r#type: r#type.to_string(),
+            config: config.to_vec(),
+        };
+
+        assert_eq!(
+            ensure_taken_over(
+                &[
+                    authenticator(2, "CosmwasmAuthenticatorV1", &[]),
+                    authenticator(4, SIGNATURE_VERIFICATION, PUBKEY),
+                ],
+                PUBKEY,
+                2
+            ),
+            Ok(())
+        );
+
+        // owner's authenticator is still there
+        assert!(ensure_taken_over(
+            &[
+                authenticator(1, SIGNATURE_VERIFICATION, &[3; 33]),
+                authenticator(2, "CosmwasmAuthenticatorV1", &[]),
+                authenticator(4, SIGNATURE_VERIFICATION, PUBKEY),
+            ],
+            PUBKEY,
+            2
+        )
+        .is_err());
+
+        // beneficiary's key was never added
+        assert!(ensure_taken_over(
+            &[authenticator(2, "CosmwasmAuthenticatorV1", &[])],
+            PUBKEY,
+            2
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use cosmwasm_std::{Coin, Coins};
+use cw_authenticator::Any;
+use osmosis_std::shim::try_proto_to_cosmwasm_coins;
+use osmosis_std::types::cosmos::bank::v1beta1::{MsgMultiSend, MsgSend};
+use osmosis_std::types::ibc::applications::transfer::v1::MsgTransfer;
+
+use super::error::{EOLError, EOLResult};
+
+/// Funds sent out of the account by a bank or IBC transfer
+#[derive(Debug, PartialEq)]
+pub struct Transfer {
+    pub recipients: Vec<String>,
+    pub amount: Vec<Coin>,
+}
+
+impl Transfer {
+    /// Decode `msg` if it is a transfer
+    pub fn decode(msg: &Any) -> EOLResult<Option<Self>> {
+        let undecodable = |_| EOLError::undecodable_message(&msg.type_url);
+
+        let transfer = match msg.type_url.as_str() {
+            MsgSend::TYPE_URL => {
+                let send = MsgSend::try_from(msg.value.clone()).map_err(undecodable)?;
+                Transfer {
+                    recipients: vec![send.to_address],
+                    amount: try_proto_to_cosmwasm_coins(send.amount).map_err(undecodable)?,
+                }
+            }
+            MsgMultiSend::TYPE_URL => {
+                let multi_send = MsgMultiSend::try_from(msg.value.clone()).map_err(undecodable)?;
+                let mut amount = Coins::default();
+                for input in multi_send.inputs {
+                    for coin in try_proto_to_cosmwasm_coins(input.coins).map_err(undecodable)? {
+                        amount.add(coin)?;
+                    }
+                }
+
+                Transfer {
+                    recipients: multi_send
+                        .outputs
+                        .into_iter()
+                        .map(|output| output.address)
+                        .collect(),
+                    amount: amount.into_vec(),
+                }
+            }
+            MsgTransfer::TYPE_URL => {
+                let transfer = MsgTransfer::try_from(msg.value.clone()).map_err(undecodable)?;
+                Transfer {
+                    recipients: vec![transfer.receiver],
+                    amount: try_proto_to_cosmwasm_coins(transfer.token).map_err(undecodable)?,
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(transfer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{coin, Binary};
+    use osmosis_std::types::cosmos::bank::v1beta1::{Input, Output};
+
+    #[test]
+    fn test_decode() {
+        let msg = Any {
+            type_url: MsgMultiSend::TYPE_URL.to_string(),
+            value: MsgMultiSend {
+                inputs: vec![
+                    Input {
+                        address: "account".to_string(),
+                        coins: vec![coin(100, "uosmo").into()],
+                    },
+                    Input {
+                        address: "account".to_string(),
+                        coins: vec![coin(50, "uosmo").into(), coin(1, "uion").into()],
+                    },
+                ],
+                outputs: vec![
+                    Output {
+                        address: "heir1".to_string(),
+                        coins: vec![],
+                    },
+                    Output {
+                        address: "heir2".to_string(),
+                        coins: vec![],
+                    },
+                ],
+            }
+            .into(),
+        };
+        assert_eq!(
+            Transfer::decode(&msg).unwrap(),
+            Some(Transfer {
+                recipients: vec!["heir1".to_string(), "heir2".to_string()],
+                amount: vec![coin(1, "uion"), coin(150, "uosmo")],
+            })
+        );
+
+        let msg = Any {
+            type_url: MsgTransfer::TYPE_URL.to_string(),
+            value: MsgTransfer {
+                receiver: "cosmos1heir".to_string(),
+                token: Some(coin(100, "uosmo").into()),
+                ..MsgTransfer::default()
+            }
+            .into(),
+        };
+        assert_eq!(
+            Transfer::decode(&msg).unwrap(),
+            Some(Transfer {
+                recipients: vec!["cosmos1heir".to_string()],
+                amount: vec![coin(100, "uosmo")],
+            })
+        );
+
+        let msg = Any {
+            type_url: "/cosmos.staking.v1beta1.MsgUndelegate".to_string(),
+            value: Binary::default(),
+        };
+        assert_eq!(Transfer::decode(&msg).unwrap(), None);
+    }
+}
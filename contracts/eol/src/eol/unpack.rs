@@ -0,0 +1,258 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{from_json, BankMsg, CosmosMsg, WasmMsg};
+use cw_authenticator::Any;
+use osmosis_std::types::cosmos::{authz::v1beta1::MsgExec, bank::v1beta1::MsgSend};
+use osmosis_std::types::cosmwasm::wasm::v1::MsgExecuteContract;
+use serde::de::IgnoredAny;
+
+use super::error::{EOLError, EOLResult};
+
+/// Maximum nesting of authz and proxied executions that gets inspected,
+/// anything nested deeper is rejected
+pub const MAX_MSG_DEPTH: usize = 4;
+
+/// cw1 style proxy execution, the only contract payload whose messages can be recovered
+#[cw_serde]
+enum ProxyMsg {
+    Execute { msgs: Vec<CosmosMsg> },
+}
+
+/// `msg` followed by every message it wraps, recursively.
+/// Wrappers that can't be decoded are rejected rather than let through uninspected.
+pub fn unpack(msg: &Any) -> EOLResult<Vec<Any>> {
+    let mut msgs = vec![];
+    unpack_into(msg, 0, &mut msgs)?;
+    Ok(msgs)
+}
+
+fn unpack_into(msg: &Any, depth: usize, msgs: &mut Vec<Any>) -> EOLResult<()> {
+    if depth > MAX_MSG_DEPTH {
+        return Err(EOLError::MsgNestingTooDeep {
+            max_depth: MAX_MSG_DEPTH,
+        });
+    }
+
+    msgs.push(msg.clone());
+    for inner in inner_msgs(msg)? {
+        unpack_into(&inner, depth + 1, msgs)?;
+    }
+
+    Ok(())
+}
+
+fn inner_msgs(msg: &Any) -> EOLResult<Vec<Any>> {
+    let undecodable = |_| EOLError::undecodable_message(&msg.type_url);
+
+    match msg.type_url.as_str() {
+        MsgExec::TYPE_URL => {
+            let exec = MsgExec::try_from(msg.value.clone()).map_err(undecodable)?;
+            Ok(exec
+                .msgs
+                .into_iter()
+                .map(|inner| Any {
+                    type_url: inner.type_url,
+                    value: inner.value.into(),
+                })
+                .collect())
+        }
+        MsgExecuteContract::TYPE_URL => {
+            let execute = MsgExecuteContract::try_from(msg.value.clone()).map_err(undecodable)?;
+
+            match from_json::<ProxyMsg>(&execute.msg) {
+                Ok(ProxyMsg::Execute { msgs }) => msgs
+                    .into_iter()
+                    .map(|inner| proxied_msg(&execute.contract, inner))
+                    .collect(),
+                // any other contract call is inspected as a whole
+                Err(_) => from_json::<IgnoredAny>(&execute.msg)
+                    .map(|_| vec![])
+                    .map_err(undecodable),
+            }
+        }
+        _ => Ok(vec![]),
+    }
+}
+
+/// Encode a message dispatched by the proxy `contract` the way the chain would see it
+fn proxied_msg(contract: &str, msg: CosmosMsg) -> EOLResult<Any> {
+    match msg {
+        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => Ok(Any {
+            type_url: MsgSend::TYPE_URL.to_string(),
+            value: MsgSend {
+                from_address: contract.to_string(),
+                to_address,
+                amount: amount.into_iter().map(Into::into).collect(),
+            }
+            .into(),
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            msg,
+            funds,
+        }) => Ok(Any {
+            type_url: MsgExecuteContract::TYPE_URL.to_string(),
+            value: MsgExecuteContract {
+                sender: contract.to_string(),
+                contract: contract_addr,
+                msg: msg.to_vec(),
+                funds: funds.into_iter().map(Into::into).collect(),
+            }
+            .into(),
+        }),
+        CosmosMsg::Stargate { type_url, value } => Ok(Any { type_url, value }),
+        _ => Err(EOLError::undecodable_message(MsgExecuteContract::TYPE_URL)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{coins, to_json_binary, Binary};
+    use osmosis_std::types::osmosis::smartaccount::v1beta1::MsgRemoveAuthenticator;
+
+    fn remove_authenticator() -> Any {
+        Any {
+            type_url: MsgRemoveAuthenticator::TYPE_URL.to_string(),
+            value: MsgRemoveAuthenticator {
+                sender: "account".to_string(),
+                id: 1,
+            }
+            .into(),
+        }
+    }
+
+    fn exec(msgs: Vec<Any>) -> Any {
+        Any {
+            type_url: MsgExec::TYPE_URL.to_string(),
+            value: MsgExec {
+                grantee: "account".to_string(),
+                msgs: msgs
+                    .into_iter()
+                    .map(|msg| osmosis_std::shim::Any {
+                        type_url: msg.type_url,
+                        value: msg.value.to_vec(),
+                    })
+                    .collect(),
+            }
+            .into(),
+        }
+    }
+
+    fn execute_contract(contract: &str, msg: Binary) -> Any {
+        Any {
+            type_url: MsgExecuteContract::TYPE_URL.to_string(),
+            value: MsgExecuteContract {
+                sender: "account".to_string(),
+                contract: contract.to_string(),
+                msg: msg.to_vec(),
+                funds: vec![],
+            }
+            .into(),
+        }
+    }
+
+    fn type_urls(msgs: Vec<Any>) -> Vec<String> {
+        msgs.into_iter().map(|msg| msg.type_url).collect()
+    }
+
+    #[test]
+    fn test_unpack_authz_exec() {
+        let msg = exec(vec![exec(vec![remove_authenticator()])]);
+
+        assert_eq!(
+            type_urls(unpack(&msg).unwrap()),
+            vec![
+                MsgExec::TYPE_URL,
+                MsgExec::TYPE_URL,
+                MsgRemoveAuthenticator::TYPE_URL
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unpack_proxy_execute() {
+        let proxied = ProxyMsg::Execute {
+            msgs: vec![
+                BankMsg::Send {
+                    to_address: "recipient".to_string(),
+                    amount: coins(100, "uosmo"),
+                }
+                .into(),
+                CosmosMsg::Stargate {
+                    type_url: remove_authenticator().type_url,
+                    value: remove_authenticator().value,
+                },
+            ],
+        };
+        let msg = execute_contract("proxy", to_json_binary(&proxied).unwrap());
+
+        let msgs = unpack(&msg).unwrap();
+        assert_eq!(
+            type_urls(msgs.clone()),
+            vec![
+                MsgExecuteContract::TYPE_URL,
+                MsgSend::TYPE_URL,
+                MsgRemoveAuthenticator::TYPE_URL
+            ]
+        );
+        assert_eq!(
+            MsgSend::try_from(msgs[1].value.clone()).unwrap(),
+            MsgSend {
+                from_address: "proxy".to_string(),
+                to_address: "recipient".to_string(),
+                amount: vec![cosmwasm_std::coin(100, "uosmo").into()],
+            }
+        );
+
+        // other contract calls are not unpacked
+        let msg = execute_contract("contract", Binary::from(br#"{"swap":{}}"#));
+        assert_eq!(
+            type_urls(unpack(&msg).unwrap()),
+            vec![MsgExecuteContract::TYPE_URL]
+        );
+    }
+
+    #[test]
+    fn test_unpack_rejects_undecodable() {
+        let msg = Any {
+            type_url: MsgExec::TYPE_URL.to_string(),
+            value: Binary::from(vec![0xff; 8]),
+        };
+        assert_eq!(
+            unpack(&msg),
+            Err(EOLError::undecodable_message(MsgExec::TYPE_URL))
+        );
+
+        let msg = execute_contract("contract", Binary::from(b"not json"));
+        assert_eq!(
+            unpack(&msg),
+            Err(EOLError::undecodable_message(MsgExecuteContract::TYPE_URL))
+        );
+
+        let proxied = ProxyMsg::Execute {
+            msgs: vec![BankMsg::Burn {
+                amount: coins(100, "uosmo"),
+            }
+            .into()],
+        };
+        let msg = execute_contract("proxy", to_json_binary(&proxied).unwrap());
+        assert_eq!(
+            unpack(&msg),
+            Err(EOLError::undecodable_message(MsgExecuteContract::TYPE_URL))
+        );
+    }
+
+    #[test]
+    fn test_unpack_depth_limit() {
+        let nested =
+            |depth: usize| (0..depth).fold(remove_authenticator(), |msg, _| exec(vec![msg]));
+
+        assert!(unpack(&nested(MAX_MSG_DEPTH)).is_ok());
+        assert_eq!(
+            unpack(&nested(MAX_MSG_DEPTH + 1)),
+            Err(EOLError::MsgNestingTooDeep {
+                max_depth: MAX_MSG_DEPTH
+            })
+        );
+    }
+}
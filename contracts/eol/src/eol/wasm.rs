@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ensure, from_json, Addr};
+use cw_authenticator::Any;
+use osmosis_std::types::cosmwasm::wasm::v1::{
+    MsgExecuteContract, MsgInstantiateContract, MsgInstantiateContract2,
+};
+use serde::de::IgnoredAny;
+
+use super::error::{EOLError, EOLResult};
+use super::params::EOLParams;
+use super::unpack::unpack;
+
+/// Entry point of this contract beneficiaries reveal themselves through
+const REVEAL_BENEFICIARY: &str = "reveal_beneficiary";
+
+/// Contract beneficiaries may execute
+#[cw_serde]
+pub struct ContractRule {
+    pub contract: String,
+
+    /// Top-level keys of the execute messages allowed, any message when unset
+    #[serde(default)]
+    pub entry_points: Option<Vec<String>>,
+}
+
+impl EOLParams {
+    /// Ensure every contract execution in `msg` is on the allowlist if any.
+    /// Beneficiaries may always reveal themselves to `this_contract`.
+    /// Under an allowlist, instantiations may not carry funds either, as the
+    /// new contract would otherwise receive them from outside the allowlist.
+    pub fn ensure_contract_call_allowed(&self, msg: &Any, this_contract: &Addr) -> EOLResult<()> {
+        let Some(allowed_contracts) = &self.allowed_contracts else {
+            return Ok(());
+        };
+
+        for msg in unpack(msg)? {
+            if let Some((code_id, funded)) = instantiation(&msg)? {
+                ensure!(!funded, EOLError::FundedInstantiationNotAllowed { code_id });
+                continue;
+            }
+            if msg.type_url != MsgExecuteContract::TYPE_URL {
+                continue;
+            }
+
+            let execute = MsgExecuteContract::try_from(msg.value.clone())
+                .map_err(|_| EOLError::undecodable_message(&msg.type_url))?;
+            let entry_point = entry_point(&execute.msg)
+                .ok_or_else(|| EOLError::undecodable_message(&msg.type_url))?;
+
+            if execute.contract == this_contract.as_str() && entry_point == REVEAL_BENEFICIARY {
+                continue;
+            }
+
+            let rule = allowed_contracts
+                .iter()
+                .find(|rule| rule.contract == execute.contract)
+                .ok_or_else(|| EOLError::contract_not_allowed(&execute.contract))?;
+
+            ensure!(
+                rule.entry_points
+                    .as_ref()
+                    .is_none_or(|entry_points| entry_points.contains(&entry_point)),
+                EOLError::EntryPointNotAllowed {
+                    contract: execute.contract,
+                    entry_point,
+                }
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Code id of an instantiation and whether it carries funds, `None` for other messages
+pub(super) fn instantiation(msg: &Any) -> EOLResult<Option<(u64, bool)>> {
+    let undecodable = |_| EOLError::undecodable_message(&msg.type_url);
+    let instantiation = match msg.type_url.as_str() {
+        MsgInstantiateContract::TYPE_URL => {
+            let msg = MsgInstantiateContract::try_from(msg.value.clone()).map_err(undecodable)?;
+            (msg.code_id, !msg.funds.is_empty())
+        }
+        MsgInstantiateContract2::TYPE_URL => {
+            let msg = MsgInstantiateContract2::try_from(msg.value.clone()).map_err(undecodable)?;
+            (msg.code_id, !msg.funds.is_empty())
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(instantiation))
+}
+
+/// Top-level key of an execute message, which names the entry point it calls
+pub(super) fn entry_point(msg: &[u8]) -> Option<String> {
+    let msg = from_json::<BTreeMap<String, IgnoredAny>>(msg).ok()?;
+    match msg.into_keys().collect::<Vec<_>>().as_slice() {
+        [key] => Some(key.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use osmosis_std::types::cosmos::base::v1beta1::Coin;
+    use rstest::rstest;
+
+    fn execute(contract: &str, msg: &str) -> Any {
+        Any {
+            type_url: MsgExecuteContract::TYPE_URL.to_string(),
+            value: MsgExecuteContract {
+                sender: "account".to_string(),
+                contract: contract.to_string(),
+                msg: msg.as_bytes().to_vec(),
+                funds: vec![],
+            }
+            .into(),
+        }
+    }
+
+    fn params() -> EOLParams {
+        EOLParams {
+            allowed_contracts: Some(vec![
+                ContractRule {
+                    contract: "vault".to_string(),
+                    entry_points: Some(vec!["withdraw".to_string()]),
+                },
+                ContractRule {
+                    contract: "pool".to_string(),
+                    entry_points: None,
+                },
+            ]),
+            ..EOLParams::default()
+        }
+    }
+
+    #[rstest]
+    #[case::allowed_entry_point("vault", r#"{"withdraw":{}}"#, Ok(()))]
+    #[case::any_entry_point("pool", r#"{"swap":{"amount":"1"}}"#, Ok(()))]
+    #[case::entry_point_not_allowed(
+        "vault",
+        r#"{"deposit":{}}"#,
+        Err(EOLError::EntryPointNotAllowed {
+            contract: "vault".to_string(),
+            entry_point: "deposit".to_string(),
+        })
+    )]
+    #[case::contract_not_allowed(
+        "other",
+        r#"{"withdraw":{}}"#,
+        Err(EOLError::contract_not_allowed("other"))
+    )]
+    #[case::reveal_beneficiary("eol", r#"{"reveal_beneficiary":{}}"#, Ok(()))]
+    #[case::other_entry_point_of_this_contract(
+        "eol",
+        r#"{"set_tracked_denoms":{}}"#,
+        Err(EOLError::contract_not_allowed("eol"))
+    )]
+    #[case::no_single_key(
+        "pool",
+        r#"{"swap":{},"withdraw":{}}"#,
+        Err(EOLError::undecodable_message(MsgExecuteContract::TYPE_URL))
+    )]
+    fn test_ensure_contract_call_allowed(
+        #[case] contract: &str,
+        #[case] msg: &str,
+        #[case] expected: EOLResult<()>,
+    ) {
+        let this_contract = Addr::unchecked("eol");
+        let msg = execute(contract, msg);
+
+        assert_eq!(
+            params().ensure_contract_call_allowed(&msg, &this_contract),
+            expected
+        );
+
+        // unrestricted without an allowlist
+        assert_eq!(
+            EOLParams::default().ensure_contract_call_allowed(&msg, &this_contract),
+            Ok(())
+        );
+    }
+
+    #[rstest]
+    #[case::instantiate(MsgInstantiateContract::TYPE_URL, vec![], Ok(()))]
+    #[case::instantiate2(MsgInstantiateContract2::TYPE_URL, vec![], Ok(()))]
+    #[case::instantiate_with_funds(
+        MsgInstantiateContract::TYPE_URL,
+        vec![Coin { denom: "uosmo".to_string(), amount: "1".to_string() }],
+        Err(EOLError::FundedInstantiationNotAllowed { code_id: 7 })
+    )]
+    #[case::instantiate2_with_funds(
+        MsgInstantiateContract2::TYPE_URL,
+        vec![Coin { denom: "uosmo".to_string(), amount: "1".to_string() }],
+        Err(EOLError::FundedInstantiationNotAllowed { code_id: 7 })
+    )]
+    fn test_ensure_instantiation_allowed(
+        #[case] type_url: &str,
+        #[case] funds: Vec<Coin>,
+        #[case] expected: EOLResult<()>,
+    ) {
+        let this_contract = Addr::unchecked("eol");
+        let value = if type_url == MsgInstantiateContract::TYPE_URL {
+            MsgInstantiateContract {
+                sender: "account".to_string(),
+                code_id: 7,
+                msg: b"{}".to_vec(),
+                funds,
+                ..MsgInstantiateContract::default()
+            }
+            .into()
+        } else {
+            MsgInstantiateContract2 {
+                sender: "account".to_string(),
+                code_id: 7,
+                msg: b"{}".to_vec(),
+                funds,
+                ..MsgInstantiateContract2::default()
+            }
+            .into()
+        };
+        let msg = Any {
+            type_url: type_url.to_string(),
+            value,
+        };
+
+        assert_eq!(
+            params().ensure_contract_call_allowed(&msg, &this_contract),
+            expected
+        );
+
+        // unrestricted without an allowlist
+        assert_eq!(
+            EOLParams::default().ensure_contract_call_allowed(&msg, &this_contract),
+            Ok(())
+        );
+    }
+}
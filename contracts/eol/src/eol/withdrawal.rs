@@ -0,0 +1,134 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ensure, Coin, StdError, Timestamp, Uint128};
+
+use super::duration::Duration;
+use super::error::{EOLError, EOLResult};
+
+/// Cap on the value beneficiaries may withdraw from the account in every `period`,
+/// expressed in the quote denom of the contract's price resolution config
+#[cw_serde]
+pub struct WithdrawalCap {
+    pub limit: Uint128,
+    pub period: Duration,
+}
+
+/// Value beneficiaries withdrew since `period_start`
+#[cw_serde]
+#[derive(Default)]
+pub struct Withdrawals {
+    pub period_start: Timestamp,
+    pub value: Uint128,
+}
+
+impl Withdrawals {
+    /// Record `value` as withdrawn at `now`. A new period starts with the first
+    /// withdrawal after the one being tracked is over, or with the very first one.
+    pub fn withdraw(
+        &mut self,
+        cap: &WithdrawalCap,
+        value: Uint128,
+        now: Timestamp,
+    ) -> EOLResult<&mut Self> {
+        if self.value.is_zero() || now >= cap.period.after(self.period_start)? {
+            *self = Self {
+                period_start: now,
+                value: Uint128::zero(),
+            };
+        }
+
+        let withdrawn = self.value.checked_add(value).map_err(StdError::from)?;
+        ensure!(
+            withdrawn <= cap.limit,
+            EOLError::WithdrawalCapExceeded { limit: cap.limit }
+        );
+
+        self.value = withdrawn;
+        Ok(self)
+    }
+}
+
+/// Amount of each denom that left the account between the `before` and `after` balances
+pub fn outflows(before: &[Coin], after: &[Coin]) -> Vec<Coin> {
+    before
+        .iter()
+        .filter_map(|coin| {
+            let remaining = after
+                .iter()
+                .find(|c| c.denom == coin.denom)
+                .map(|c| c.amount)
+                .unwrap_or_default();
+
+            (remaining < coin.amount).then(|| Coin {
+                denom: coin.denom.clone(),
+                amount: coin.amount - remaining,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::coin;
+
+    #[test]
+    fn test_withdraw() {
+        let cap = WithdrawalCap {
+            limit: Uint128::new(10_000),
+            period: Duration::from_seconds(604_800).unwrap(), // a week
+        };
+        let start = Timestamp::from_seconds(1_000);
+        let mut withdrawals = Withdrawals::default();
+
+        withdrawals
+            .withdraw(&cap, Uint128::new(6_000), start)
+            .unwrap();
+        assert_eq!(
+            withdrawals
+                .clone()
+                .withdraw(&cap, Uint128::new(4_001), start.plus_seconds(604_799))
+                .unwrap_err(),
+            EOLError::WithdrawalCapExceeded {
+                limit: Uint128::new(10_000)
+            }
+        );
+
+        withdrawals
+            .withdraw(&cap, Uint128::new(4_000), start.plus_seconds(604_799))
+            .unwrap();
+        assert_eq!(withdrawals.value, Uint128::new(10_000));
+
+        // next week
+        withdrawals
+            .withdraw(&cap, Uint128::new(1), start.plus_seconds(604_800))
+            .unwrap();
+        assert_eq!(
+            withdrawals,
+            Withdrawals {
+                period_start: start.plus_seconds(604_800),
+                value: Uint128::new(1),
+            }
+        );
+
+        // a period running past the latest timestamp errors out rather than panic
+        let endless = WithdrawalCap {
+            period: Duration::from_nanos(u64::MAX),
+            ..cap
+        };
+        assert!(matches!(
+            withdrawals.withdraw(&endless, Uint128::new(1), start.plus_seconds(604_801)),
+            Err(EOLError::Std(_))
+        ));
+    }
+
+    #[test]
+    fn test_outflows() {
+        assert_eq!(
+            outflows(
+                &[coin(100, "ugov"), coin(100, "uosmo"), coin(100, "uion")],
+                &[coin(40, "ugov"), coin(200, "uosmo")]
+            ),
+            vec![coin(60, "ugov"), coin(100, "uion")]
+        );
+    }
+}
@@ -4,6 +4,8 @@ use cosmwasm_std::{CoinsError, StdError, Timestamp};
 
 use crate::{
     authenticator::AuthenticatorError,
+    eol::{error::EOLError, Duration},
+    price::PriceError,
 };
 
 /// Never is a placeholder to ensure we don't return any errors
@@ -37,6 +39,24 @@ pub enum ContractError {
     #[error("Authenticator error: {0}")]
     AuthenticatorError(#[from] AuthenticatorError),
 
+    #[error("EOL error: {0}")]
+    EOLError(#[from] EOLError),
+
+    #[error("Price error: {0}")]
+    PriceError(#[from] PriceError),
+
     #[error("Requested entry not found")]
     NotFound {},
+
+    #[error("Cannot migrate from contract {contract}")]
+    ContractMismatch { contract: String },
+
+    #[error("Cannot downgrade from {from} to {to}")]
+    Downgrade { from: String, to: String },
+
+    #[error("Admin operations are timelocked for {timelock}, queue them instead")]
+    Timelocked { timelock: Duration },
+
+    #[error("Admin operation is not executable before {executable_at}")]
+    AdminOpNotReady { executable_at: Timestamp },
 }
@@ -4,10 +4,14 @@ pub mod authenticator;
 pub mod eol;
 
 pub mod admin;
+pub mod config;
 
 pub mod contract;
 pub mod error;
+pub mod migrate;
 pub mod msg;
+pub mod pause;
+pub mod price;
 pub mod state;
 
 #[cfg(test)]
@@ -0,0 +1,243 @@
+use cosmwasm_std::{ensure, Env, Order, StdError, StdResult, Storage};
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
+
+use crate::state::EOLS;
+use crate::ContractError;
+
+/// State transform bringing the state stored by prior versions up to `version`
+pub struct Migration {
+    pub version: &'static str,
+    pub transform: fn(&mut dyn Storage, &Env) -> StdResult<()>,
+}
+
+/// Transforms run on migration, in ascending version order
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: "1.1.0",
+    transform: rewrite_inactivity_periods,
+}];
+
+/// Rewrite EOLs stored with their inactivity period as `Timestamp` nanos
+/// under `inactivity_time_period` into a `Duration` under `inactivity_period`
+fn rewrite_inactivity_periods(storage: &mut dyn Storage, _env: &Env) -> StdResult<()> {
+    let eols = EOLS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for ((account, id), eol) in eols {
+        EOLS.save(storage, (&account, &id), &eol)?;
+    }
+
+    Ok(())
+}
+
+/// Run the `migrations` newer than the stored version, up to and including `version`,
+/// then record `version`. Returns the versions whose transforms ran.
+pub fn migrate_state(
+    storage: &mut dyn Storage,
+    env: &Env,
+    contract: &str,
+    version: &str,
+    migrations: &[Migration],
+) -> Result<Vec<&'static str>, ContractError> {
+    let stored = get_contract_version(storage)?;
+    ensure!(
+        stored.contract == contract,
+        ContractError::ContractMismatch {
+            contract: stored.contract
+        }
+    );
+
+    let from = parse_version(&stored.version)?;
+    let to = parse_version(version)?;
+    ensure!(
+        from <= to,
+        ContractError::Downgrade {
+            from: stored.version,
+            to: version.to_string()
+        }
+    );
+
+    let mut applied = vec![];
+    for migration in migrations {
+        let at = parse_version(migration.version)?;
+        if from < at && at <= to {
+            (migration.transform)(storage, env)?;
+            applied.push(migration.version);
+        }
+    }
+
+    set_contract_version(storage, contract, version)?;
+
+    Ok(applied)
+}
+
+fn parse_version(version: &str) -> StdResult<Version> {
+    Version::parse(version)
+        .map_err(|e| StdError::generic_err(format!("invalid version {version}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eol::{Duration, EOL};
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::{testing::mock_env, Addr, Timestamp};
+
+    const CONTRACT: &str = "crates.io:eol";
+
+    /// EOL as stored by 1.0.0, before any of the fields that default on load
+    const LEGACY_EOL: &str =
+        r#"{"inactivity_time_period":"100000000000","last_spent_at":"1571797419879305533"}"#;
+
+    fn fixture(version: &str) -> MockStorage {
+        let mut storage = MockStorage::new();
+        set_contract_version(&mut storage, CONTRACT, version).unwrap();
+        for id in ["1", "2.0"] {
+            let key = EOLS.key((&Addr::unchecked("account"), id));
+            storage.set(&key, LEGACY_EOL.as_bytes());
+        }
+        storage
+    }
+
+    /// Doubles every inactivity period
+    fn double_inactivity_periods(storage: &mut dyn Storage, _env: &Env) -> StdResult<()> {
+        let eols = EOLS
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for ((account, id), mut eol) in eols {
+            eol.inactivity_period = Duration::from_nanos(eol.inactivity_period.nanos() * 2);
+            EOLS.save(storage, (&account, &id), &eol)?;
+        }
+
+        Ok(())
+    }
+
+    /// Starts over the inactivity of every EOL
+    fn reset_last_spent(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+        let eols = EOLS
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for ((account, id), mut eol) in eols {
+            eol.last_spent_at = env.block.time;
+            EOLS.save(storage, (&account, &id), &eol)?;
+        }
+
+        Ok(())
+    }
+
+    const FIXTURE_MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: "1.0.0",
+            transform: reset_last_spent,
+        },
+        Migration {
+            version: "1.1.0",
+            transform: double_inactivity_periods,
+        },
+        Migration {
+            version: "1.2.0",
+            transform: double_inactivity_periods,
+        },
+    ];
+
+    #[test]
+    fn test_migrate_state() {
+        let env = mock_env();
+        let mut storage = fixture("1.0.0");
+
+        let applied =
+            migrate_state(&mut storage, &env, CONTRACT, "1.1.0", FIXTURE_MIGRATIONS).unwrap();
+
+        // only the transforms after the stored version and up to the new one ran
+        assert_eq!(applied, vec!["1.1.0"]);
+        assert_eq!(get_contract_version(&storage).unwrap().version, "1.1.0");
+        for id in ["1", "2.0"] {
+            let eol = EOLS
+                .load(&storage, (&Addr::unchecked("account"), id))
+                .unwrap();
+            assert_eq!(
+                eol,
+                EOL::new(
+                    Duration::from_seconds(200).unwrap(),
+                    Timestamp::from_nanos(1_571_797_419_879_305_533)
+                )
+            );
+        }
+
+        // migrating to the same version again is a no-op
+        let applied =
+            migrate_state(&mut storage, &env, CONTRACT, "1.1.0", FIXTURE_MIGRATIONS).unwrap();
+        assert!(applied.is_empty());
+
+        // older states catch up through every transform in order
+        let mut storage = fixture("0.9.0");
+        let applied =
+            migrate_state(&mut storage, &env, CONTRACT, "1.2.0", FIXTURE_MIGRATIONS).unwrap();
+        assert_eq!(applied, vec!["1.0.0", "1.1.0", "1.2.0"]);
+        let eol = EOLS
+            .load(&storage, (&Addr::unchecked("account"), "1"))
+            .unwrap();
+        assert_eq!(
+            eol,
+            EOL::new(Duration::from_seconds(400).unwrap(), env.block.time)
+        );
+    }
+
+    #[test]
+    fn test_migrate_state_refuses_downgrade() {
+        let mut storage = fixture("1.1.0");
+
+        assert_eq!(
+            migrate_state(
+                &mut storage,
+                &mock_env(),
+                CONTRACT,
+                "1.0.0",
+                FIXTURE_MIGRATIONS
+            )
+            .unwrap_err(),
+            ContractError::Downgrade {
+                from: "1.1.0".to_string(),
+                to: "1.0.0".to_string()
+            }
+        );
+        assert_eq!(get_contract_version(&storage).unwrap().version, "1.1.0");
+    }
+
+    #[test]
+    fn test_migrate_state_refuses_other_contract() {
+        let mut storage = MockStorage::new();
+        set_contract_version(&mut storage, "crates.io:other", "0.1.0").unwrap();
+
+        assert_eq!(
+            migrate_state(&mut storage, &mock_env(), CONTRACT, "1.0.0", MIGRATIONS).unwrap_err(),
+            ContractError::ContractMismatch {
+                contract: "crates.io:other".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_migrate_inactivity_periods() {
+        let mut storage = fixture("1.0.0");
+
+        let applied =
+            migrate_state(&mut storage, &mock_env(), CONTRACT, "1.1.0", MIGRATIONS).unwrap();
+
+        assert_eq!(applied, vec!["1.1.0"]);
+        let key = EOLS.key((&Addr::unchecked("account"), "1"));
+        let stored = String::from_utf8(storage.get(&key).unwrap()).unwrap();
+        assert!(stored.contains(r#""inactivity_period":"100s""#));
+        assert!(!stored.contains("inactivity_time_period"));
+    }
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        let versions = MIGRATIONS
+            .iter()
+            .map(|migration| parse_version(migration.version).unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
@@ -1,13 +1,22 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Uint64;
+use cosmwasm_std::{Binary, Uint64};
 pub use osmosis_std::types::osmosis::poolmanager::v1beta1::SwapAmountInRoute;
 
+use crate::admin::{AdminOp, PendingAdminOp};
+use crate::authenticator::AuthenticatorNode;
+use crate::config::Config;
 use crate::eol::eol::EOL;
+use crate::eol::{BeneficiaryKey, Duration};
+use crate::pause::{PauseScope, PauseStatus};
 
-use crate::eol::EOLStore;
+use crate::eol::{AssetTransfers, EOLStore};
+use crate::price::{PriceInfo, PriceResolutionConfig};
 // re-export the structs from cw_authenticator
 pub use cw_authenticator::AuthenticatorSudoMsg as SudoMsg;
 
+#[cw_serde]
+pub struct MigrateMsg {}
+
 #[cw_serde]
 pub struct TrackedDenom {
     pub denom: String,
@@ -23,6 +32,21 @@ pub enum DenomRemovalTarget {
 #[cw_serde]
 pub struct InstantiateMsg {
     pub admin: Option<String>,
+
+    /// Price resolution used to value withdrawals against `EOLParams::withdrawal_cap`
+    #[serde(default)]
+    pub price_resolution_config: Option<PriceResolutionConfig>,
+
+    #[serde(default)]
+    pub tracked_denoms: Vec<TrackedDenom>,
+
+    /// Bounds on the params of every EOL authenticator
+    #[serde(default)]
+    pub config: Option<Config>,
+
+    /// Delay admin operations are held back for, see [`ExecuteMsg::QueueAdminOp`]
+    #[serde(default)]
+    pub admin_timelock: Option<Duration>,
 }
 
 #[cw_serde]
@@ -34,6 +58,55 @@ pub enum ExecuteMsg {
     RejectAdminTransfer {},
     CancelAdminTransfer {},
     RevokeAdmin {},
+    /// Set how prices are resolved. Changing the quote denom untracks every denom.
+    /// Admin only.
+    SetPriceResolutionConfig {
+        price_resolution_config: PriceResolutionConfig,
+    },
+    /// Track or re-route the prices of `tracked_denoms`. Admin only.
+    SetTrackedDenoms {
+        tracked_denoms: Vec<TrackedDenom>,
+    },
+    /// Admin only.
+    RemoveTrackedDenoms {
+        target: DenomRemovalTarget,
+    },
+    /// Queue `op` to take effect once the admin timelock went by.
+    /// Required for every admin operation but pauses while a timelock is set. Admin only.
+    QueueAdminOp {
+        op: AdminOp,
+    },
+    /// Apply a queued operation whose timelock went by. Admin only.
+    ExecuteAdminOp {
+        id: u64,
+    },
+    /// Drop a queued operation. Admin only.
+    CancelAdminOp {
+        id: u64,
+    },
+    /// Set the bounds on the params of EOL authenticators added from now on. Admin only.
+    SetConfig {
+        config: Config,
+    },
+    /// Stop `scope` until unpaused, owners keep authenticating either way.
+    /// Applies right away even while a timelock is set. Admin only.
+    Pause {
+        scope: PauseScope,
+    },
+    /// Resume `scope`. Admin only.
+    Unpause {
+        scope: PauseScope,
+    },
+    /// Reveal a beneficiary committed to in the authenticator params, along with
+    /// the key `address` derives from, which it then authenticates with.
+    /// Only allowed once the account is dormant.
+    RevealBeneficiary {
+        account: String,
+        authenticator_id: String,
+        address: String,
+        pubkey: BeneficiaryKey,
+        salt: Binary,
+    },
 }
 
 #[cw_serde]
@@ -48,11 +121,50 @@ pub enum QueryMsg {
     #[returns(EOLsByAccountResponse)]
     EOLs { account: String },
 
+    /// EOLs of every authenticator under the root authenticator `root`
+    #[returns(EOLsByRootResponse)]
+    EOLsByRoot { account: String, root: u64 },
+
     #[returns(AdminResponse)]
     Admin {},
 
     #[returns(AdminCandidateResponse)]
     AdminCandidate {},
+
+    #[returns(AdminTimelockResponse)]
+    AdminTimelock {},
+
+    #[returns(PendingAdminOpsResponse)]
+    PendingAdminOps {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    #[returns(ConfigResponse)]
+    Config {},
+
+    #[returns(PauseStatusResponse)]
+    PauseStatus {},
+
+    #[returns(PriceResolutionConfigResponse)]
+    PriceResolutionConfig {},
+
+    #[returns(TrackedDenomsResponse)]
+    TrackedDenoms {},
+
+    #[returns(AssetTransfersResponse)]
+    AssetTransfers {
+        account: String,
+        authenticator_id: String,
+    },
+
+    /// Decoded configuration of the account's authenticator,
+    /// the subtree at `authenticator_id` when it is a composite id
+    #[returns(AuthenticatorTreeResponse)]
+    AuthenticatorTree {
+        account: String,
+        authenticator_id: String,
+    },
 }
 
 #[cw_serde]
@@ -65,6 +177,11 @@ pub struct EOLsByAccountResponse {
     pub eols: Vec<(String, EOL)>,
 }
 
+#[cw_serde]
+pub struct EOLsByRootResponse {
+    pub eols: Vec<(String, EOL)>,
+}
+
 #[cw_serde]
 pub struct AdminResponse {
     pub admin: Option<String>,
@@ -74,3 +191,43 @@ pub struct AdminResponse {
 pub struct AdminCandidateResponse {
     pub candidate: Option<String>,
 }
+
+#[cw_serde]
+pub struct AdminTimelockResponse {
+    pub timelock: Duration,
+}
+
+#[cw_serde]
+pub struct PendingAdminOpsResponse {
+    pub ops: Vec<(u64, PendingAdminOp)>,
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub config: Option<Config>,
+}
+
+#[cw_serde]
+pub struct PauseStatusResponse {
+    pub status: PauseStatus,
+}
+
+#[cw_serde]
+pub struct PriceResolutionConfigResponse {
+    pub price_resolution_config: Option<PriceResolutionConfig>,
+}
+
+#[cw_serde]
+pub struct TrackedDenomsResponse {
+    pub tracked_denoms: Vec<(String, PriceInfo)>,
+}
+
+#[cw_serde]
+pub struct AssetTransfersResponse {
+    pub asset_transfers: Vec<(String, AssetTransfers)>,
+}
+
+#[cw_serde]
+pub struct AuthenticatorTreeResponse {
+    pub tree: AuthenticatorNode,
+}
@@ -0,0 +1,92 @@
+use std::fmt;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ensure, Storage};
+
+use crate::authenticator::AuthenticatorError;
+use crate::state::PAUSE_STATUS;
+
+/// What the admin can pause without affecting the owners' access to their accounts
+#[cw_serde]
+#[derive(Copy)]
+pub enum PauseScope {
+    /// Authentication of every signer but the owner
+    Beneficiaries,
+
+    /// Registration of new EOL authenticators
+    Registrations,
+}
+
+impl fmt::Display for PauseScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PauseScope::Beneficiaries => write!(f, "beneficiary authentication"),
+            PauseScope::Registrations => write!(f, "authenticator registration"),
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct PauseStatus {
+    pub beneficiaries: bool,
+    pub registrations: bool,
+}
+
+impl PauseStatus {
+    pub fn is_paused(&self, scope: PauseScope) -> bool {
+        match scope {
+            PauseScope::Beneficiaries => self.beneficiaries,
+            PauseScope::Registrations => self.registrations,
+        }
+    }
+
+    pub fn set(&mut self, scope: PauseScope, paused: bool) {
+        match scope {
+            PauseScope::Beneficiaries => self.beneficiaries = paused,
+            PauseScope::Registrations => self.registrations = paused,
+        }
+    }
+}
+
+/// Ensure `scope` is not paused by the admin
+pub fn ensure_not_paused(
+    storage: &dyn Storage,
+    scope: PauseScope,
+) -> Result<(), AuthenticatorError> {
+    let status = PAUSE_STATUS.may_load(storage)?.unwrap_or_default();
+    ensure!(
+        !status.is_paused(scope),
+        AuthenticatorError::Paused { scope }
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    #[test]
+    fn test_ensure_not_paused() {
+        let mut storage = MockStorage::new();
+        assert!(ensure_not_paused(&storage, PauseScope::Beneficiaries).is_ok());
+
+        let mut status = PauseStatus::default();
+        status.set(PauseScope::Beneficiaries, true);
+        PAUSE_STATUS.save(&mut storage, &status).unwrap();
+
+        assert_eq!(
+            ensure_not_paused(&storage, PauseScope::Beneficiaries).unwrap_err(),
+            AuthenticatorError::Paused {
+                scope: PauseScope::Beneficiaries
+            }
+        );
+        assert!(ensure_not_paused(&storage, PauseScope::Registrations).is_ok());
+
+        status.set(PauseScope::Beneficiaries, false);
+        PAUSE_STATUS.save(&mut storage, &status).unwrap();
+        assert!(ensure_not_paused(&storage, PauseScope::Beneficiaries).is_ok());
+    }
+}
@@ -0,0 +1,51 @@
+use cosmwasm_std::{StdError, Timestamp};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum PriceError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Price resolution is not configured")]
+    NotConfigured {},
+
+    #[error("Swap routes of {denom} must end in the quote denom {quote_denom}")]
+    InvalidSwapRoutes { denom: String, quote_denom: String },
+
+    #[error(
+        "Failed to query twap of pool {pool_id} for {base_denom}/{quote_denom} since {start_time}"
+    )]
+    TwapQueryError {
+        pool_id: u64,
+        base_denom: String,
+        quote_denom: String,
+        start_time: Timestamp,
+    },
+
+    #[error("Denom {denom} is not tracked and can't be valued")]
+    UntrackedDenom { denom: String },
+
+    #[error("Price of {denom} is stale since {last_updated_time} and could not be refreshed")]
+    StalePrice {
+        denom: String,
+        last_updated_time: Timestamp,
+    },
+}
+
+impl PriceError {
+    pub fn twap_query_error(
+        pool_id: u64,
+        base_denom: &str,
+        quote_denom: &str,
+        start_time: Timestamp,
+    ) -> Self {
+        Self::TwapQueryError {
+            pool_id,
+            base_denom: base_denom.to_string(),
+            quote_denom: quote_denom.to_string(),
+            start_time,
+        }
+    }
+}
+
+pub type PriceResult<T> = Result<T, PriceError>;
@@ -0,0 +1,364 @@
+mod error;
+
+use std::str::FromStr;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    ensure, Coin, Decimal, QuerierWrapper, StdError, Storage, Timestamp, Uint128, Uint64,
+};
+use cw_storage_plus::Map;
+use osmosis_std::types::osmosis::{
+    poolmanager::v1beta1::SwapAmountInRoute, twap::v1beta1::TwapQuerier,
+};
+
+use crate::state::PRICE_INFOS;
+
+pub use error::{PriceError, PriceResult};
+
+/// How prices of tracked denoms are resolved, managed by the contract admin
+#[cw_serde]
+pub struct PriceResolutionConfig {
+    /// Denom all prices are expressed in
+    pub quote_denom: String,
+
+    /// Duration in nanoseconds a cached price can be used for before being refreshed
+    pub staleness_threshold: Uint64,
+
+    /// Duration in nanoseconds the arithmetic TWAP is taken over
+    pub twap_duration: Uint64,
+}
+
+/// Cached price of a tracked denom in the quote denom
+#[cw_serde]
+pub struct PriceInfo {
+    pub price: Decimal,
+    pub last_updated_time: Timestamp,
+    pub swap_routes: Vec<SwapAmountInRoute>,
+}
+
+pub type PriceInfoStore<'a> = Map<'a, &'a str, PriceInfo>;
+
+impl PriceResolutionConfig {
+    pub fn validate(&self) -> PriceResult<()> {
+        ensure!(
+            !self.quote_denom.is_empty(),
+            StdError::generic_err("quote denom must not be empty")
+        );
+        ensure!(
+            !self.twap_duration.is_zero(),
+            StdError::generic_err("twap duration must not be zero")
+        );
+
+        Ok(())
+    }
+
+    /// Resolve the price of `denom` along `swap_routes`, which must end in the quote denom
+    pub fn track_denom(
+        &self,
+        querier: &QuerierWrapper,
+        denom: &str,
+        swap_routes: Vec<SwapAmountInRoute>,
+        now: Timestamp,
+    ) -> PriceResult<PriceInfo> {
+        ensure!(
+            swap_routes
+                .last()
+                .is_some_and(|route| route.token_out_denom == self.quote_denom),
+            PriceError::InvalidSwapRoutes {
+                denom: denom.to_string(),
+                quote_denom: self.quote_denom.clone(),
+            }
+        );
+
+        Ok(PriceInfo {
+            price: self.twap_price(querier, denom, &swap_routes, now)?,
+            last_updated_time: now,
+            swap_routes,
+        })
+    }
+
+    /// Product of the arithmetic TWAPs along `swap_routes`
+    fn twap_price(
+        &self,
+        querier: &QuerierWrapper,
+        denom: &str,
+        swap_routes: &[SwapAmountInRoute],
+        now: Timestamp,
+    ) -> PriceResult<Decimal> {
+        let start_time = now.minus_nanos(self.twap_duration.u64());
+        let twap_querier = TwapQuerier::new(querier);
+
+        let mut base_denom = denom;
+        let mut price = Decimal::one();
+        for route in swap_routes {
+            let twap = twap_querier
+                .arithmetic_twap_to_now(
+                    route.pool_id,
+                    base_denom.to_string(),
+                    route.token_out_denom.clone(),
+                    Some(osmosis_std::shim::Timestamp {
+                        seconds: start_time.seconds() as i64,
+                        nanos: start_time.subsec_nanos() as i32,
+                    }),
+                )
+                .ok()
+                .and_then(|res| Decimal::from_str(&res.arithmetic_twap).ok())
+                .ok_or_else(|| {
+                    PriceError::twap_query_error(
+                        route.pool_id,
+                        base_denom,
+                        &route.token_out_denom,
+                        start_time,
+                    )
+                })?;
+
+            price = price.checked_mul(twap).map_err(StdError::from)?;
+            base_denom = &route.token_out_denom;
+        }
+
+        Ok(price)
+    }
+
+    /// Price of `denom` at `now`, refreshing and caching it once stale.
+    /// Prices refreshed while simulating are not cached.
+    /// Denoms that are not tracked have no price.
+    pub fn price(
+        &self,
+        storage: &mut dyn Storage,
+        querier: &QuerierWrapper,
+        denom: &str,
+        now: Timestamp,
+        simulate: bool,
+    ) -> PriceResult<Option<Decimal>> {
+        if denom == self.quote_denom {
+            return Ok(Some(Decimal::one()));
+        }
+
+        let Some(price_info) = PRICE_INFOS.may_load(storage, denom)? else {
+            return Ok(None);
+        };
+
+        if now
+            <= price_info
+                .last_updated_time
+                .plus_nanos(self.staleness_threshold.u64())
+        {
+            return Ok(Some(price_info.price));
+        }
+
+        let price = self
+            .twap_price(querier, denom, &price_info.swap_routes, now)
+            .map_err(|_| PriceError::StalePrice {
+                denom: denom.to_string(),
+                last_updated_time: price_info.last_updated_time,
+            })?;
+        if !simulate {
+            PRICE_INFOS.save(
+                storage,
+                denom,
+                &PriceInfo {
+                    price,
+                    last_updated_time: now,
+                    ..price_info
+                },
+            )?;
+        }
+
+        Ok(Some(price))
+    }
+
+    /// Value of `coins` in the quote denom, rounded up.
+    /// Fails on denoms that are not tracked rather than counting them as worthless.
+    pub fn value(
+        &self,
+        storage: &mut dyn Storage,
+        querier: &QuerierWrapper,
+        coins: &[Coin],
+        now: Timestamp,
+        simulate: bool,
+    ) -> PriceResult<Uint128> {
+        let mut value = Uint128::zero();
+        for coin in coins {
+            let price = self
+                .price(storage, querier, &coin.denom, now, simulate)?
+                .ok_or_else(|| PriceError::UntrackedDenom {
+                    denom: coin.denom.clone(),
+                })?;
+            let coin_value = coin
+                .amount
+                .checked_mul_ceil(price)
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+            value = value.checked_add(coin_value).map_err(StdError::from)?;
+        }
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helper::mock_stargate_querier::{
+        arithmetic_twap_to_now_query_handler, mock_dependencies_with_stargate_querier,
+    };
+    use cosmwasm_std::{coin, ContractResult};
+    use osmosis_std::types::osmosis::twap::v1beta1::ArithmeticTwapToNowResponse;
+
+    const UUSDC: &str = "uusdc";
+
+    fn config() -> PriceResolutionConfig {
+        PriceResolutionConfig {
+            quote_denom: UUSDC.to_string(),
+            staleness_threshold: 3_600_000_000_000u64.into(), // 1h
+            twap_duration: 3_600_000_000_000u64.into(),       // 1h
+        }
+    }
+
+    fn route(pool_id: u64, token_out_denom: &str) -> SwapAmountInRoute {
+        SwapAmountInRoute {
+            pool_id,
+            token_out_denom: token_out_denom.to_string(),
+        }
+    }
+
+    fn deps() -> cosmwasm_std::OwnedDeps<
+        cosmwasm_std::testing::MockStorage,
+        cosmwasm_std::testing::MockApi,
+        crate::test_helper::mock_stargate_querier::MockStargateQuerier,
+    > {
+        mock_dependencies_with_stargate_querier(
+            &[],
+            arithmetic_twap_to_now_query_handler(Box::new(|req| {
+                let arithmetic_twap = match (
+                    req.pool_id,
+                    req.base_asset.as_str(),
+                    req.quote_asset.as_str(),
+                ) {
+                    (1, "uosmo", UUSDC) => "1.5",
+                    (2, "uion", "uosmo") => "4",
+                    _ => return ContractResult::Err("Price not found".to_string()),
+                }
+                .to_string();
+
+                ContractResult::Ok(ArithmeticTwapToNowResponse { arithmetic_twap })
+            })),
+        )
+    }
+
+    #[test]
+    fn test_track_denom() {
+        let deps = deps();
+        let querier = QuerierWrapper::new(&deps.querier);
+        let now = Timestamp::from_seconds(10_000);
+
+        assert_eq!(
+            config()
+                .track_denom(
+                    &querier,
+                    "uion",
+                    vec![route(2, "uosmo"), route(1, UUSDC)],
+                    now
+                )
+                .unwrap()
+                .price,
+            Decimal::from_str("6").unwrap()
+        );
+
+        // route does not end in the quote denom
+        assert_eq!(
+            config()
+                .track_denom(&querier, "uion", vec![route(2, "uosmo")], now)
+                .unwrap_err(),
+            PriceError::InvalidSwapRoutes {
+                denom: "uion".to_string(),
+                quote_denom: UUSDC.to_string(),
+            }
+        );
+
+        // wrong pool
+        assert_eq!(
+            config()
+                .track_denom(
+                    &querier,
+                    "uion",
+                    vec![route(1, "uosmo"), route(1, UUSDC)],
+                    now
+                )
+                .unwrap_err(),
+            PriceError::twap_query_error(1, "uion", "uosmo", now.minus_seconds(3_600))
+        );
+    }
+
+    #[test]
+    fn test_value() {
+        let mut deps = deps();
+        let now = Timestamp::from_seconds(10_000);
+        let stale_price = PriceInfo {
+            price: Decimal::one(),
+            last_updated_time: now.minus_seconds(3_601),
+            swap_routes: vec![route(1, UUSDC)],
+        };
+        PRICE_INFOS
+            .save(deps.as_mut().storage, "uosmo", &stale_price)
+            .unwrap();
+
+        let storage = &mut deps.storage;
+        let querier = QuerierWrapper::new(&deps.querier);
+
+        // uosmo price gets refreshed, yet only cached outside of simulations
+        let value = |storage: &mut dyn Storage, simulate: bool| {
+            config()
+                .value(
+                    storage,
+                    &querier,
+                    &[coin(100, "uosmo"), coin(7, UUSDC)],
+                    now,
+                    simulate,
+                )
+                .unwrap()
+        };
+        assert_eq!(value(storage, true), Uint128::new(157));
+        assert_eq!(PRICE_INFOS.load(storage, "uosmo").unwrap(), stale_price);
+        assert_eq!(value(storage, false), Uint128::new(157));
+        assert_eq!(
+            PRICE_INFOS.load(storage, "uosmo").unwrap(),
+            PriceInfo {
+                price: Decimal::from_str("1.5").unwrap(),
+                last_updated_time: now,
+                ..stale_price
+            }
+        );
+
+        // untracked denoms can't be valued
+        assert_eq!(
+            config()
+                .value(storage, &querier, &[coin(1_000, "uatom")], now, false)
+                .unwrap_err(),
+            PriceError::UntrackedDenom {
+                denom: "uatom".to_string()
+            }
+        );
+
+        // stale price that can't be refreshed
+        PRICE_INFOS
+            .save(
+                storage,
+                "uion",
+                &PriceInfo {
+                    price: Decimal::one(),
+                    last_updated_time: now.minus_seconds(3_601),
+                    swap_routes: vec![route(3, UUSDC)],
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            config()
+                .value(storage, &querier, &[coin(1, "uion")], now, false)
+                .unwrap_err(),
+            PriceError::StalePrice {
+                denom: "uion".to_string(),
+                last_updated_time: now.minus_seconds(3_601),
+            }
+        );
+    }
+}
@@ -1,12 +1,71 @@
-use cw_storage_plus::{Item, Map};
+use std::str::FromStr;
 
-use crate::{
-    admin::Admin,
+use cosmwasm_std::{Addr, Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Item, Map};
 
-};
-use crate::eol::EOLStore;
+use crate::admin::{Admin, PendingAdminOp};
+use crate::authenticator::CompositeId;
+use crate::config::Config;
+use crate::eol::{AssetTransfersStore, Duration, EOLStore, SessionStore, EOL};
+use crate::pause::PauseStatus;
+use crate::price::{PriceInfoStore, PriceResolutionConfig};
 
 pub const EOLS: EOLStore<'_> = Map::new("eols");
 
+/// Sessions of the messages currently being authenticated and executed.
+pub const SESSIONS: SessionStore<'_> = Map::new("sessions");
+
+/// cw20 and cw721 tokens each beneficiary moved out of the accounts.
+pub const ASSET_TRANSFERS: AssetTransfersStore<'_> = Map::new("asset_transfers");
+
+/// Price resolution config used to value beneficiary withdrawals, managed by the admin.
+pub const PRICE_RESOLUTION_CONFIG: Item<PriceResolutionConfig> =
+    Item::new("price_resolution_config");
+
+/// Cached prices of the tracked denoms.
+pub const PRICE_INFOS: PriceInfoStore<'_> = Map::new("price_infos");
+
+/// Bounds on the params of every EOL authenticator, managed by the admin.
+/// Params are unbounded when unset.
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// What the admin paused, nothing when unset.
+pub const PAUSE_STATUS: Item<PauseStatus> = Item::new("pause_status");
+
 /// Admin address, Optional.
 pub const ADMIN: Item<Admin> = Item::new("admin");
+
+/// Delay admin operations are held back for, none when unset.
+pub const ADMIN_TIMELOCK: Item<Duration> = Item::new("admin_timelock");
+
+/// Admin operations waiting on the timelock, by id.
+/// Dropped when the admin changes hands.
+pub const PENDING_ADMIN_OPS: Map<u64, PendingAdminOp> = Map::new("pending_admin_ops");
+
+/// Id of the next admin operation queued.
+pub const NEXT_ADMIN_OP_ID: Item<u64> = Item::new("next_admin_op_id");
+
+/// EOLs of `account` kept for the authenticator `id` and every authenticator nested under it
+pub fn eols_under(
+    storage: &dyn Storage,
+    account: &Addr,
+    id: &CompositeId,
+) -> StdResult<Vec<(String, EOL)>> {
+    // composite ids only hold digits and dots, so "{id}" up to "{id}/" spans `id` and the
+    // ids with more path segments, along with siblings such as "5.10" for "5.1"
+    let start = id.to_string();
+    let end = format!("{start}/");
+
+    EOLS.prefix(account)
+        .range(
+            storage,
+            Some(Bound::inclusive(start.as_str())),
+            Some(Bound::exclusive(end.as_str())),
+            Order::Ascending,
+        )
+        .filter(|item| match item {
+            Ok((key, _)) => CompositeId::from_str(key).is_ok_and(|key| id.contains(&key)),
+            Err(_) => true,
+        })
+        .collect()
+}
@@ -13,18 +13,18 @@ use serde::Serialize;
 
 use crate::{
     authenticator::{CosmwasmAuthenticatorData, SubAuthenticatorData},
+    eol::EOLParams,
     msg::InstantiateMsg,
-    spend_limit::SpendLimitParams,
 };
 
-pub fn spend_limit_store_code(wasm: &Wasm<'_, OsmosisTestApp>, acc: &SigningAccount) -> u64 {
+pub fn eol_store_code(wasm: &Wasm<'_, OsmosisTestApp>, acc: &SigningAccount) -> u64 {
     let wasm_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
         .join("..")
         .join("..")
         .join("target")
         .join("wasm32-unknown-unknown")
         .join("release")
-        .join("spend_limit.wasm");
+        .join("eol.wasm");
 
     let wasm_bytes = std::fs::read(wasm_path).unwrap();
 
@@ -34,30 +34,23 @@ pub fn spend_limit_store_code(wasm: &Wasm<'_, OsmosisTestApp>, acc: &SigningAcco
         .code_id
 }
 
-pub fn spend_limit_instantiate(
+pub fn eol_instantiate(
     wasm: &Wasm<'_, OsmosisTestApp>,
     code_id: u64,
     msg: &InstantiateMsg,
     acc: &SigningAccount,
 ) -> String {
-    wasm.instantiate(
-        code_id,
-        msg,
-        None,
-        Some("spend_limit_authenticator"),
-        &[],
-        acc,
-    )
-    .unwrap()
-    .data
-    .address
+    wasm.instantiate(code_id, msg, None, Some("eol_authenticator"), &[], acc)
+        .unwrap()
+        .data
+        .address
 }
 
-pub fn add_spend_limit_authenticator(
+pub fn add_eol_authenticator(
     app: &OsmosisTestApp,
     acc: &SigningAccount,
     contract: &str,
-    params: &SpendLimitParams,
+    params: &EOLParams,
 ) -> u64 {
     add_authenticator(
         app,
@@ -70,11 +63,11 @@ pub fn add_spend_limit_authenticator(
     )
 }
 
-pub fn add_all_of_sig_ver_spend_limit_authenticator(
+pub fn add_all_of_sig_ver_eol_authenticator(
     app: &OsmosisTestApp,
     acc: &SigningAccount,
     contract: &str,
-    params: &SpendLimitParams,
+    params: &EOLParams,
 ) -> u64 {
     add_authenticator(
         app,
@@ -98,62 +91,6 @@ pub fn add_all_of_sig_ver_spend_limit_authenticator(
     )
 }
 
-/// Add 1-click trading authenticator, it is
-/// AllOf(
-///     SignatureVerification,
-///     CosmwasmAuthenticatorV1(SpendLimit)),
-///     AnyOf(
-///         MessageFilterAuthenticator({"@type":"/osmosis.poolmanager.v1beta1.MsgSwapExactAmountIn"})
-///         MessageFilterAuthenticator({"@type":"/osmosis.poolmanager.v1beta1.MsgSplitRouteSwapExactAmountIn"})
-///     )
-pub fn add_1ct_session_authenticator(
-    app: &OsmosisTestApp,
-    acc: &SigningAccount,
-    session_pubkey: &[u8],
-    spend_limit_contract: &str,
-    params: &SpendLimitParams,
-) -> u64 {
-    add_authenticator(
-        app,
-        acc,
-        "AllOf",
-        &[
-            SubAuthenticatorData {
-                r#type: "SignatureVerification".to_string(),
-                config: session_pubkey.to_vec(),
-            },
-            SubAuthenticatorData {
-                r#type: "CosmwasmAuthenticatorV1".to_string(),
-                config: to_json_binary(&CosmwasmAuthenticatorData {
-                    contract: spend_limit_contract.to_string(),
-                    params: to_json_binary(params).unwrap().to_vec(),
-                })
-                .unwrap()
-                .to_vec(),
-            },
-            SubAuthenticatorData {
-                r#type: "AnyOf".to_string(),
-                config: to_json_binary(&[
-                    SubAuthenticatorData {
-                        r#type: "MessageFilter".to_string(),
-                        config: r#"{"@type":"/osmosis.poolmanager.v1beta1.MsgSwapExactAmountIn"}"#
-                            .as_bytes()
-                            .to_vec(),
-                    },
-                    SubAuthenticatorData {
-                        r#type: "MessageFilter".to_string(),
-                        config: r#"{"@type":"/osmosis.poolmanager.v1beta1.MsgSplitRouteSwapExactAmountIn"}"#
-                            .as_bytes()
-                            .to_vec(),
-                    },
-                ])
-                .unwrap()
-                .to_vec(),
-            },
-        ],
-    )
-}
-
 pub fn add_authenticator<T>(
     app: &OsmosisTestApp,
     acc: &SigningAccount,
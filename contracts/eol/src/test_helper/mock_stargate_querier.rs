@@ -4,16 +4,26 @@ use std::marker::PhantomData;
 
 use cosmwasm_std::{
     from_json,
-    testing::{MockApi, MockQuerier, MockStorage},
-    to_json_binary, Binary, Coin, ContractResult, CustomQuery, Empty, OwnedDeps, Querier,
-    QuerierResult, QuerierWrapper, QueryRequest, SystemError, SystemResult,
+    testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR},
+    to_json_binary, to_json_vec, Binary, Coin, ContractResult, CustomQuery, Empty, OwnedDeps,
+    Querier, QuerierResult, QuerierWrapper, QueryRequest, SystemError, SystemResult,
+};
+use osmosis_std::types::cosmos::staking::v1beta1::{
+    DelegationResponse, QueryDelegatorDelegationsRequest, QueryDelegatorDelegationsResponse,
+    QueryDelegatorUnbondingDelegationsRequest, QueryDelegatorUnbondingDelegationsResponse,
+    UnbondingDelegation,
 };
 use osmosis_std::types::osmosis::{
-    smartaccount::v1beta1::{GetAuthenticatorRequest, GetAuthenticatorResponse},
+    smartaccount::v1beta1::{
+        AccountAuthenticator, GetAuthenticatorRequest, GetAuthenticatorResponse,
+        GetAuthenticatorsRequest, GetAuthenticatorsResponse,
+    },
     twap::v1beta1::{ArithmeticTwapToNowRequest, ArithmeticTwapToNowResponse, TwapQuerier},
 };
 use serde::{de::DeserializeOwned, Serialize};
 
+use crate::authenticator::{CosmwasmAuthenticatorData, COSMWASM_AUTHENTICATOR};
+
 type QueryHandler = Box<dyn Fn(String, Binary) -> QuerierResult>;
 
 pub struct MockStargateQuerier<C: DeserializeOwned = Empty> {
@@ -34,6 +44,10 @@ impl<C: DeserializeOwned> MockStargateQuerier<C> {
         self
     }
 
+    pub fn update_stargate_handler(&mut self, handler: QueryHandler) {
+        self.stargate_query_handler = Some(handler);
+    }
+
     pub fn update_balance(&mut self, addr: impl Into<String>, balance: Vec<Coin>) {
         self.mock_querier.update_balance(addr, balance);
     }
@@ -98,6 +112,95 @@ pub fn get_authenticator_query_handler(
     )
 }
 
+pub fn get_authenticators_query_handler(
+    req_mapper: Box<dyn Fn(GetAuthenticatorsRequest) -> ContractResult<GetAuthenticatorsResponse>>,
+) -> QueryHandler {
+    make_query_handler(
+        "/osmosis.smartaccount.v1beta1.Query/GetAuthenticators",
+        req_mapper,
+    )
+}
+
+/// Authenticator queries, answering for any account and id with a cosmwasm authenticator
+/// of `contract` configured with `params`
+pub fn registered_params_query_handler(contract: &str, params: &Binary) -> QueryHandler {
+    let contract = contract.to_string();
+    let params = params.to_vec();
+    get_authenticator_query_handler(Box::new(move |req| {
+        ContractResult::Ok(GetAuthenticatorResponse {
+            account_authenticator: Some(AccountAuthenticator {
+                id: req.authenticator_id,
+                r#type: COSMWASM_AUTHENTICATOR.to_string(),
+                config: to_json_vec(&CosmwasmAuthenticatorData {
+                    contract: contract.clone(),
+                    params: params.clone(),
+                })
+                .unwrap(),
+            }),
+        })
+    }))
+}
+
+/// Try `handler` first, falling back to `fallback` for the queries it doesn't support
+pub fn chain_query_handlers(handler: QueryHandler, fallback: QueryHandler) -> QueryHandler {
+    Box::new(
+        move |path: String, data: Binary| match handler(path.clone(), data.clone()) {
+            SystemResult::Err(SystemError::UnsupportedRequest { .. }) => fallback(path, data),
+            res => res,
+        },
+    )
+}
+
+/// Dependencies whose authenticator queries answer with `params` registered for the mock contract
+pub fn mock_dependencies_with_registered_params(
+    balances: &[Coin],
+    params: &Binary,
+) -> OwnedDeps<MockStorage, MockApi, MockStargateQuerier, Empty> {
+    mock_dependencies_with_stargate_querier(
+        &[(MOCK_CONTRACT_ADDR, balances)],
+        registered_params_query_handler(MOCK_CONTRACT_ADDR, params),
+    )
+}
+
+/// Delegator staking queries, answering with a single delegation and unbonding delegation
+/// when `delegated` and `unbonding` respectively
+pub fn delegator_staking_query_handler(delegated: bool, unbonding: bool) -> QueryHandler {
+    let delegations = make_query_handler(
+        "/cosmos.staking.v1beta1.Query/DelegatorDelegations",
+        Box::new(move |_: QueryDelegatorDelegationsRequest| {
+            ContractResult::Ok(QueryDelegatorDelegationsResponse {
+                delegation_responses: if delegated {
+                    vec![DelegationResponse::default()]
+                } else {
+                    vec![]
+                },
+                pagination: None,
+            })
+        }),
+    );
+    let unbonding_delegations = make_query_handler(
+        "/cosmos.staking.v1beta1.Query/DelegatorUnbondingDelegations",
+        Box::new(move |_: QueryDelegatorUnbondingDelegationsRequest| {
+            ContractResult::Ok(QueryDelegatorUnbondingDelegationsResponse {
+                unbonding_responses: if unbonding {
+                    vec![UnbondingDelegation::default()]
+                } else {
+                    vec![]
+                },
+                pagination: None,
+            })
+        }),
+    );
+
+    Box::new(move |path: String, data: Binary| {
+        if path.ends_with("/DelegatorDelegations") {
+            delegations(path, data)
+        } else {
+            unbonding_delegations(path, data)
+        }
+    })
+}
+
 fn make_query_handler<Req, Res>(
     target_path: &'static str,
     req_mapper: Box<dyn Fn(Req) -> ContractResult<Res>>,